@@ -0,0 +1,157 @@
+//! Procedural macros of the [sha256](https://docs.rs/sha256) crate, use them through its
+//! `macros` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use sha2::{Digest, Sha256};
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Fields, Lit, LitInt, Member,
+};
+
+/// Computes the sha256 digest of a string or byte string literal at compile time, as `[u8; 32]`.
+#[proc_macro]
+pub fn sha256(input: TokenStream) -> TokenStream {
+    match literal_digest(input) {
+        Ok(digest) => quote!([#(#digest),*]).into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Computes the sha256 digest of a string or byte string literal at compile time, as a
+/// lowercase hex `&'static str`.
+#[proc_macro]
+pub fn sha256_hex(input: TokenStream) -> TokenStream {
+    match literal_digest(input) {
+        Ok(digest) => {
+            let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+            quote!(#hex).into()
+        }
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+fn literal_digest(input: TokenStream) -> syn::Result<[u8; 32]> {
+    let lit: Lit = syn::parse(input)?;
+    let bytes = match &lit {
+        Lit::Str(s) => s.value().into_bytes(),
+        Lit::ByteStr(s) => s.value(),
+        _ => {
+            return Err(syn::Error::new(
+                lit.span(),
+                "expected a string or byte string literal",
+            ))
+        }
+    };
+    Ok(Sha256::digest(bytes).into())
+}
+
+/// Derives `sha256::Sha256Hashable`, see its documentation for the encoding.
+#[proc_macro_derive(Sha256Hashable, attributes(sha256))]
+pub fn derive_sha256_hashable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_hashable(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_hashable(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let mut version = 0u32;
+    for attr in sha256_attrs(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("version") {
+                version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported sha256 attribute, expected `version`"))
+            }
+        })?;
+    }
+
+    let name = &input.ident;
+    let type_name = name.unraw().to_string();
+    let arms = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, fields) = hash_fields(&data.fields)?;
+            vec![quote!(Self #pattern => { #fields hasher })]
+        }
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                let variant_name = ident.unraw().to_string();
+                let (pattern, fields) = hash_fields(&variant.fields)?;
+                Ok(quote! {
+                    Self::#ident #pattern => {
+                        let hasher = hasher.field(#variant_name);
+                        #fields
+                        hasher
+                    }
+                })
+            })
+            .collect::<syn::Result<_>>()?,
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span,
+                "Sha256Hashable can't be derived for unions",
+            ))
+        }
+    };
+
+    for param in input.generics.type_params_mut() {
+        param.bounds.push(parse_quote!(::sha256::Sha256Hashable));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::sha256::Sha256Hashable for #name #ty_generics #where_clause {
+            fn hash_fields(&self, hasher: ::sha256::FieldHasher) -> ::sha256::FieldHasher {
+                let hasher = hasher.field(#type_name).field(#version.to_be_bytes());
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+/// The pattern binding the hashed `fields`, and the statements hashing them into `hasher`.
+fn hash_fields(fields: &Fields) -> syn::Result<(TokenStream2, TokenStream2)> {
+    let mut bindings = Vec::new();
+    let mut calls = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        if is_skipped(&field.attrs)? {
+            continue;
+        }
+        let (member, name) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.unraw().to_string()),
+            None => (Member::Unnamed(i.into()), i.to_string()),
+        };
+        let binding = format_ident!("field_{}", i);
+        bindings.push(quote!(#member: ref #binding));
+        calls.push(quote! {
+            let hasher = ::sha256::Sha256Hashable::hash_fields(#binding, hasher.field(#name));
+        });
+    }
+    Ok((quote!({ #(#bindings,)* .. }), quote!(#(#calls)*)))
+}
+
+fn is_skipped(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in sha256_attrs(attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported sha256 attribute, expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+fn sha256_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("sha256"))
+}
@@ -0,0 +1,174 @@
+#[cfg(feature = "tar")]
+use crate::dir::{file_mode, Walk};
+use crate::{calc_raw, Sha256Hash};
+#[cfg(feature = "tar")]
+use crate::{lower_hex, DirOptions};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+#[cfg(feature = "tar")]
+use std::fs;
+use std::io;
+use std::io::Read;
+#[cfg(feature = "zip")]
+use std::io::Seek;
+#[cfg(feature = "tar")]
+use std::io::Write;
+#[cfg(feature = "tar")]
+use std::path::Path;
+use std::path::PathBuf;
+
+/// sha256 digest every regular file inside a tar archive, without extracting it
+///
+/// The archive is read in one pass, so `reader` can be a pipe or a decompressor. Directories,
+/// links and other special entries are skipped. An entry appearing twice keeps its last digest,
+/// as extracting the archive would.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_tar_entries;
+/// let mut builder = tar::Builder::new(vec![]);
+/// let mut header = tar::Header::new_gnu();
+/// header.set_size(5);
+/// builder.append_data(&mut header, "hello.txt", &b"hello"[..]).unwrap();
+/// let archive = builder.into_inner().unwrap();
+///
+/// let digests = digest_tar_entries(&archive[..]).unwrap();
+/// assert_eq!(digests[std::path::Path::new("hello.txt")].to_hex(),"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// ```
+#[cfg(feature = "tar")]
+pub fn digest_tar_entries<R: Read>(reader: R) -> io::Result<BTreeMap<PathBuf, Sha256Hash>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut digests = BTreeMap::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let hash = calc_raw(entry, Sha256::new())?;
+        digests.insert(path, hash.into());
+    }
+    Ok(digests)
+}
+
+/// sha256 digest every file inside a zip archive, without extracting it
+///
+/// Entries are decompressed on the fly; directories are skipped. Entry names are kept as stored
+/// in the archive, they are not checked for `..` or absolute paths.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_zip_entries;
+/// use std::io::{Cursor, Write};
+/// let mut writer = zip::ZipWriter::new(Cursor::new(vec![]));
+/// writer.start_file("hello.txt", zip::write::SimpleFileOptions::default()).unwrap();
+/// writer.write_all(b"hello").unwrap();
+/// let archive = writer.finish().unwrap();
+///
+/// let digests = digest_zip_entries(archive).unwrap();
+/// assert_eq!(digests[std::path::Path::new("hello.txt")].to_hex(),"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// ```
+#[cfg(feature = "zip")]
+pub fn digest_zip_entries<R: Read + Seek>(reader: R) -> io::Result<BTreeMap<PathBuf, Sha256Hash>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut digests = BTreeMap::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let path = PathBuf::from(entry.name());
+        let hash = calc_raw(entry, Sha256::new())?;
+        digests.insert(path, hash.into());
+    }
+    Ok(digests)
+}
+
+/// Write the files under `root` selected by `options` as a normalized tar archive
+///
+/// The archive is byte for byte reproducible: entries come in path order, modification times are
+/// 0, owners are 0 with no names, and modes are `0644`, or `0755` for files with an executable
+/// bit. Symlinks are stored as links when `options` hashes symlinks and followed otherwise.
+/// Only files are stored, not directories.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{write_normalized_tar, DirOptions};
+/// let archive = write_normalized_tar("./src", vec![], &DirOptions::new()).unwrap();
+/// assert_eq!(archive.len() % 512, 0);
+/// ```
+#[cfg(feature = "tar")]
+pub fn write_normalized_tar<P, W>(root: P, writer: W, options: &DirOptions) -> io::Result<W>
+where
+    P: AsRef<Path>,
+    W: Write,
+{
+    let mut builder = tar::Builder::new(writer);
+    builder.mode(tar::HeaderMode::Deterministic);
+    for entry in Walk::with_options(root.as_ref(), options.clone()) {
+        let entry = entry?;
+        let mut header = tar::Header::new_gnu();
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        if entry.symlink {
+            let target = fs::read_link(&entry.path)?;
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_mode(0o777);
+            header.set_size(0);
+            builder.append_link(&mut header, &entry.rel, target)?;
+            continue;
+        }
+        let file = fs::File::open(&entry.path)?;
+        let meta = file.metadata()?;
+        let mode = if file_mode(&meta) & 0o111 != 0 {
+            0o755
+        } else {
+            0o644
+        };
+        header.set_mode(mode);
+        header.set_size(meta.len());
+        builder.append_data(&mut header, &entry.rel, file.take(meta.len()))?;
+    }
+    builder.into_inner()
+}
+
+/// sha256 digest the normalized tar archive of a directory, see [`write_normalized_tar`]
+///
+/// The digest is the same as the one of the archive written by [`write_normalized_tar`], so it
+/// identifies a tree across machines and can be checked with `sha256sum` on the archive.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, try_digest_normalized_tar, write_normalized_tar, DirOptions};
+/// let options = DirOptions::new();
+/// let archive = write_normalized_tar("./src", vec![], &options).unwrap();
+/// assert_eq!(try_digest_normalized_tar("./src", &options).unwrap(), digest(&archive));
+/// ```
+#[cfg(feature = "tar")]
+pub fn try_digest_normalized_tar<P: AsRef<Path>>(
+    root: P,
+    options: &DirOptions,
+) -> io::Result<String> {
+    let writer = write_normalized_tar(root, HashWriter(Sha256::new()), options)?;
+    Ok(lower_hex::encode(writer.0.finalize()))
+}
+
+#[cfg(feature = "tar")]
+struct HashWriter(Sha256);
+
+#[cfg(feature = "tar")]
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,6 +1,15 @@
-use crate::{CalculatorSelector, TrySha256Digest};
+use crate::{lower_hex, CalculatorSelector, DEFAULT_BUFFER_SIZE};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{
+    symlink_digest, Error, Operation, Options, Sha256Hash, SymlinkPolicy, TrySha256Digest,
+};
 use bytes::BytesMut;
+use futures_util::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use std::future::Future;
 use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
 
 /// sha256 digest file
 ///
@@ -16,10 +25,77 @@ use std::io;
 /// });
 ///
 /// ```
+#[cfg(not(target_arch = "wasm32"))]
 pub async fn try_async_digest<D: TrySha256Digest>(input: D) -> Result<String, D::Error> {
     input.async_digest().await
 }
 
+/// sha256 digest file, as set up by `options`
+///
+/// Only the symlink policy, `max_bytes`, `buffer_size` and `drop_page_cache` apply here.
+/// `direct_io`, `aligned_buffer`, `rate_limit` and `pipelined` are ignored: the file is read
+/// through tokio's own buffering, use [`try_digest_with`](crate::try_digest_with) on a blocking
+/// thread for those.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{try_async_digest_with, Options};
+/// tokio_test::block_on(async{
+/// let options = Options::new().buffer_size(1 << 20);
+/// let val = try_async_digest_with("./foo.file", &options).await.unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1")
+/// });
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn try_async_digest_with<P: AsRef<Path>>(
+    path: P,
+    options: &Options,
+) -> Result<String, Error> {
+    use tokio::io::AsyncReadExt;
+
+    let path = path.as_ref();
+    if options.symlinks != SymlinkPolicy::Follow {
+        let meta = tokio::fs::symlink_metadata(path)
+            .await
+            .map_err(|e| Error::io(Operation::Metadata, path, e))?;
+        if meta.is_symlink() {
+            if options.symlinks == SymlinkPolicy::Error {
+                return Err(Error::Symlink(path.to_path_buf()));
+            }
+            let target = tokio::fs::read_link(path)
+                .await
+                .map_err(|e| Error::io(Operation::ReadLink, path, e))?;
+            return Ok(symlink_digest(&target));
+        }
+    }
+    let f = async_open_file(path).await?;
+    let limit = options.max_bytes.unwrap_or(u64::MAX);
+    let too_large = || Error::TooLarge {
+        path: path.to_path_buf(),
+        limit,
+    };
+    let meta = f
+        .metadata()
+        .await
+        .map_err(|e| Error::io(Operation::Metadata, path, e))?;
+    if options.max_bytes.is_some() && meta.len() > limit {
+        return Err(too_large());
+    }
+    let buffer_size = options.buffer_size_for(&meta);
+    let mut input = f.take(limit.saturating_add(1));
+    let (hash, _) = async_calc_raw_sized(Unbuffered(&mut input), Sha256::new(), buffer_size)
+        .await
+        .map_err(|(e, offset)| Error::read(path, offset, e))?;
+    if options.drop_page_cache {
+        crate::os::drop_page_cache(input.get_ref());
+    }
+    if input.limit() == 0 {
+        return Err(too_large());
+    }
+    Ok(lower_hex::encode(hash))
+}
+
 /// sha256 digest file
 ///
 /// # Examples
@@ -33,35 +109,331 @@ pub async fn try_async_digest<D: TrySha256Digest>(input: D) -> Result<String, D:
 /// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1")
 /// });
 /// ```
-#[cfg(feature = "native_openssl")]
+#[cfg(all(feature = "native_openssl", not(target_arch = "wasm32")))]
 pub async fn try_async_openssl_digest<D: TrySha256Digest>(input: D) -> Result<String, D::Error> {
     input.async_openssl_digest().await
 }
 
-#[async_trait::async_trait]
+/// Check a file against its expected digest, streaming it and comparing in constant time
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{async_verify_file, Sha256Hash};
+/// tokio_test::block_on(async{
+/// let expected: Sha256Hash = "433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1".parse().unwrap();
+/// assert!(async_verify_file("./foo.file", &expected).await.unwrap());
+/// });
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn async_verify_file<P: AsRef<Path>>(
+    path: P,
+    expected: &Sha256Hash,
+) -> Result<bool, Error> {
+    let hash = async_calc_path(path.as_ref(), Sha256::new()).await?;
+    Ok(Sha256Hash::from(hash).ct_eq(expected))
+}
+
+/// Like [`async_verify_file`], failing fast without reading the file when its length isn't
+/// `expected_len`
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn async_verify_file_len<P: AsRef<Path>>(
+    path: P,
+    expected: &Sha256Hash,
+    expected_len: u64,
+) -> Result<bool, Error> {
+    let path = path.as_ref();
+    let f = async_open_file(path).await?;
+    let meta = f.metadata().await;
+    if meta
+        .map_err(|e| Error::io(Operation::Metadata, path, e))?
+        .len()
+        != expected_len
+    {
+        return Ok(false);
+    }
+    let hash = async_calc_raw_offset(Unbuffered(f), Sha256::new())
+        .await
+        .map(|(hash, _)| hash)
+        .map_err(|(e, offset)| Error::read(path, offset, e))?;
+    Ok(Sha256Hash::from(hash).ct_eq(expected))
+}
+
+/// sha256 digest many files, with at most `max_concurrency` files hashed at the same time
+///
+/// Results are returned in the same order as `paths`. Failures are [`Error`]s, like those of
+/// [`try_async_digest_with`] and `par_digest_files`, and convert into `io::Error` with `?`
+/// where one is expected.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_files;
+/// use std::path::PathBuf;
+/// tokio_test::block_on(async{
+/// let paths = vec![PathBuf::from("./foo.file"), PathBuf::from("./Cargo.toml")];
+/// let res = digest_files(paths, 4).await;
+/// assert_eq!(res[0].0, PathBuf::from("./foo.file"));
+/// assert_eq!(res[0].1.as_ref().unwrap(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1")
+/// });
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn digest_files<I: IntoIterator<Item = PathBuf>>(
+    paths: I,
+    max_concurrency: usize,
+) -> Vec<(PathBuf, Result<String, Error>)> {
+    futures_util::stream::iter(paths)
+        .map(|path| async move {
+            let res = try_async_digest(&path).await;
+            (path, res)
+        })
+        .buffered(max_concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// sha256 digest every file under a directory, yielding `(path, digest)` as each file is done
+///
+/// Entries of a directory are visited in file name order. Symlinks to files are hashed, symlinks
+/// to directories are not descended into. A directory that can't be read yields its own path
+/// with the error.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_dir_stream;
+/// use futures_util::StreamExt;
+/// tokio_test::block_on(async{
+/// let mut stream = Box::pin(digest_dir_stream("./src"));
+/// while let Some((path, res)) = stream.next().await {
+///     println!("{} {}", res.unwrap(), path.display());
+/// }
+/// });
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn digest_dir_stream<P: AsRef<Path>>(
+    root: P,
+) -> impl Stream<Item = (PathBuf, Result<String, Error>)> {
+    futures_util::stream::unfold(
+        vec![root.as_ref().to_path_buf()],
+        |mut pending| async move {
+            while let Some(path) = pending.pop() {
+                let meta = match tokio::fs::symlink_metadata(&path).await {
+                    Ok(meta) => meta,
+                    Err(e) => {
+                        let e = Error::io(Operation::Metadata, &path, e);
+                        return Some(((path, Err(e)), pending));
+                    }
+                };
+                if meta.is_dir() {
+                    match read_dir_sorted(&path).await {
+                        Ok(entries) => pending.extend(entries.into_iter().rev()),
+                        Err(e) => {
+                            let e = Error::io(Operation::ReadDir, &path, e);
+                            return Some(((path, Err(e)), pending));
+                        }
+                    }
+                    continue;
+                }
+                if meta.file_type().is_symlink() {
+                    match tokio::fs::metadata(&path).await {
+                        Ok(meta) if meta.is_file() => {}
+                        _ => continue,
+                    }
+                } else if !meta.is_file() {
+                    // FIFOs, sockets and devices, which could block or never end.
+                    continue;
+                }
+                let res = try_async_digest(&path).await;
+                return Some(((path, res), pending));
+            }
+            None
+        },
+    )
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_dir_sorted(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dir = tokio::fs::read_dir(path).await?;
+    let mut entries = vec![];
+    while let Some(entry) = dir.next_entry().await? {
+        entries.push(entry.path());
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// sha256 digest any async input, e.g. a child process' stdout
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sha256::try_async_digest_input;
+/// use std::process::Stdio;
+/// use tokio::process::Command;
+/// tokio_test::block_on(async{
+/// let mut child = Command::new("cat").arg("./foo.file").stdout(Stdio::piped()).spawn().unwrap();
+/// let val = try_async_digest_input(child.stdout.take().unwrap()).await.unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1")
+/// });
+/// ```
+pub async fn try_async_digest_input<I: AsyncCalculatorInput>(input: I) -> io::Result<String> {
+    async_calc(input, Sha256::new()).await
+}
+
+/// sha256 digest the piped stdin of this process
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sha256::try_async_digest_stdin;
+/// tokio_test::block_on(async{
+/// let val = try_async_digest_stdin().await.unwrap();
+/// println!("{val}  -");
+/// });
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn try_async_digest_stdin() -> io::Result<String> {
+    try_async_digest_input(tokio::io::stdin()).await
+}
+
+/// sha256 digest a stream of chunks
+///
+/// Unlike the file based functions this one doesn't need `tokio::fs`, so it is also available
+/// on `wasm32` targets.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::async_digest_chunks;
+/// tokio_test::block_on(async{
+/// let chunks = futures_util::stream::iter(vec!["hel", "lo"]);
+/// let val = async_digest_chunks(chunks).await;
+/// assert_eq!(val,"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+/// });
+/// ```
+pub async fn async_digest_chunks<S>(chunks: S) -> String
+where
+    S: Stream,
+    S::Item: AsRef<[u8]>,
+{
+    let sha = chunks
+        .fold(Sha256::new(), |mut sha, chunk| async move {
+            sha.update(chunk.as_ref());
+            sha
+        })
+        .await;
+    lower_hex::encode(sha.finalize())
+}
+
+/// sha256 digest a stream of fallible chunks, stopping at the first error
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_async_digest_chunks;
+/// tokio_test::block_on(async{
+/// let chunks = futures_util::stream::iter(vec![Ok::<_, ()>("hel"), Ok("lo")]);
+/// let val = try_async_digest_chunks(chunks).await.unwrap();
+/// assert_eq!(val,"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+/// });
+/// ```
+pub async fn try_async_digest_chunks<S, B, E>(chunks: S) -> Result<String, E>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    let mut chunks = std::pin::pin!(chunks);
+    let mut sha = Sha256::new();
+    while let Some(chunk) = chunks.next().await {
+        sha.update(chunk?.as_ref());
+    }
+    Ok(lower_hex::encode(sha.finalize()))
+}
+
 pub trait AsyncCalculatorInput {
-    async fn read_inner(&mut self, buf: &mut BytesMut) -> io::Result<usize>;
+    fn read_inner(&mut self, buf: &mut BytesMut) -> impl Future<Output = io::Result<usize>> + Send;
 }
 
-pub async fn async_calc<I, S>(mut input: I, mut selector: S) -> io::Result<String>
+pub async fn async_calc<I, S>(input: I, selector: S) -> io::Result<String>
 where
     I: AsyncCalculatorInput,
     S: CalculatorSelector,
 {
-    let mut buf = BytesMut::with_capacity(1024);
+    let hash = async_calc_raw(input, selector).await?;
+    Ok(lower_hex::encode(hash))
+}
+
+async fn async_calc_raw<I, S>(input: I, selector: S) -> io::Result<S::FinishType>
+where
+    I: AsyncCalculatorInput,
+    S: CalculatorSelector,
+{
+    async_calc_raw_offset(input, selector)
+        .await
+        .map(|(hash, _)| hash)
+        .map_err(|(e, _)| e)
+}
+
+/// Like `async_calc_raw`, also giving back how many bytes were hashed, up to a read error if any.
+async fn async_calc_raw_offset<I, S>(
+    input: I,
+    selector: S,
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: AsyncCalculatorInput,
+    S: CalculatorSelector,
+{
+    async_calc_raw_sized(input, selector, DEFAULT_BUFFER_SIZE).await
+}
+
+/// Like `async_calc_raw_offset`, reading up to `buffer_size` bytes at a time.
+pub(crate) async fn async_calc_raw_sized<I, S>(
+    mut input: I,
+    mut selector: S,
+    buffer_size: usize,
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: AsyncCalculatorInput,
+    S: CalculatorSelector,
+{
+    // Allocated once, `read_inner` fills its spare capacity after each `clear`.
+    let mut buf = BytesMut::with_capacity(buffer_size.max(1));
+    let mut offset = 0;
     loop {
         buf.clear();
-        let len = input.read_inner(&mut buf).await?;
+        let len = input.read_inner(&mut buf).await.map_err(|e| (e, offset))?;
         if len == 0 {
             break;
         }
         selector.update_inner(&buf[0..len]);
+        offset += len as u64;
     }
-    let hash = selector.finish_inner();
-    Ok(hex::encode(hash))
+    Ok((selector.finish_inner(), offset))
+}
+
+/// Open and hash the file at `path`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn async_calc_path<S>(path: &Path, selector: S) -> Result<S::FinishType, Error>
+where
+    S: CalculatorSelector,
+{
+    let f = async_open_file(path).await?;
+    async_calc_raw_offset(Unbuffered(f), selector)
+        .await
+        .map(|(hash, _)| hash)
+        .map_err(|(e, offset)| Error::read(path, offset, e))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn async_open_file(path: &Path) -> Result<tokio::fs::File, Error> {
+    let f = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| Error::io(Operation::Open, path, e))?;
+    crate::os::advise_sequential(&f);
+    Ok(f)
 }
 
-#[async_trait::async_trait]
 impl<R> AsyncCalculatorInput for tokio::io::BufReader<R>
 where
     R: tokio::io::AsyncRead + Unpin + Send,
@@ -72,3 +444,46 @@ where
         self.read_buf(buf).await
     }
 }
+
+/// Input read straight into the hashing buffer, without the extra copy of a `BufReader`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct Unbuffered<R>(pub(crate) R);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<R> AsyncCalculatorInput for Unbuffered<R>
+where
+    R: tokio::io::AsyncRead + Unpin + Send,
+{
+    async fn read_inner(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+        use tokio::io::AsyncReadExt;
+
+        self.0.read_buf(buf).await
+    }
+}
+
+macro_rules! impl_async_calculator_input {
+    ($($ty:ty),*) => {
+        $(
+            impl AsyncCalculatorInput for $ty {
+                async fn read_inner(&mut self, buf: &mut BytesMut) -> io::Result<usize> {
+                    use tokio::io::AsyncReadExt;
+
+                    self.read_buf(buf).await
+                }
+            }
+        )*
+    };
+}
+
+impl_async_calculator_input!(tokio::io::DuplexStream);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl_async_calculator_input!(
+    tokio::io::Stdin,
+    tokio::process::ChildStdout,
+    tokio::process::ChildStderr,
+    tokio::net::TcpStream
+);
+
+#[cfg(unix)]
+impl_async_calculator_input!(tokio::net::UnixStream);
@@ -0,0 +1,159 @@
+use crate::{open_file, Error, Operation, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Copy `src` to `dst`, hashing the bytes as they are copied
+///
+/// The copy is written next to `dst` and only renamed over it when its digest is `expected`, so
+/// `dst` is never left with unverified content: on a mismatch the copy is deleted and
+/// [`Error::Mismatch`] is returned. Permissions are copied as by `fs::copy`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{copy_verified, Sha256Hash};
+/// let expected: Sha256Hash = "433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1".parse().unwrap();
+/// let dst = std::env::temp_dir().join("sha256_doc_copy_verified");
+/// assert_eq!(copy_verified("./foo.file", &dst, &expected).unwrap(), expected);
+/// ```
+pub fn copy_verified<P, Q>(src: P, dst: Q, expected: &Sha256Hash) -> Result<Sha256Hash, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let (src, dst) = (src.as_ref(), dst.as_ref());
+    let reader = open_file(src)?;
+    let perms = reader
+        .metadata()
+        .map_err(|e| Error::io(Operation::Metadata, src, e))?
+        .permissions();
+    let mut tmp = TempFile::new(dst)?;
+    let hash = tmp.write_hashed(reader, |offset, e| Error::read(src, offset, e))?;
+    if !hash.ct_eq(expected) {
+        return Err(Error::Mismatch {
+            path: src.to_path_buf(),
+            expected: *expected,
+            actual: hash,
+        });
+    }
+    fs::set_permissions(&tmp.path, perms).map_err(|e| Error::io(Operation::Write, &tmp.path, e))?;
+    tmp.persist(dst)?;
+    Ok(hash)
+}
+
+/// Write everything `reader` yields to `path` atomically, returning its digest
+///
+/// The data goes to a temporary file next to `path` while being hashed, is synced to disk, and
+/// the file is then renamed into place: readers of `path` see either the old content or the
+/// whole new one. On error the temporary file is deleted and `path` is untouched.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, write_with_digest};
+/// let path = std::env::temp_dir().join("sha256_doc_write_with_digest");
+/// let hash = write_with_digest(&path, &b"hello"[..]).unwrap();
+/// assert_eq!(hash.to_hex(), digest("hello"));
+/// assert_eq!(std::fs::read(&path).unwrap(), b"hello");
+/// ```
+pub fn write_with_digest<P: AsRef<Path>, R: Read>(path: P, reader: R) -> Result<Sha256Hash, Error> {
+    let path = path.as_ref();
+    let mut tmp = TempFile::new(path)?;
+    let hash = tmp.write_hashed(reader, |offset, source| Error::Io {
+        source,
+        path: None,
+        op: Some(Operation::Read),
+        offset: Some(offset),
+    })?;
+    tmp.persist(path)?;
+    Ok(hash)
+}
+
+/// A file created next to its final destination, deleted on drop unless persisted.
+pub(crate) struct TempFile {
+    path: PathBuf,
+    file: Option<fs::File>,
+}
+
+impl TempFile {
+    pub(crate) fn new(dst: &Path) -> Result<Self, Error> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = dst.file_name().unwrap_or_default().to_string_lossy();
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp_name = format!(".{}.{}.{}.tmp", name, std::process::id(), n);
+        let path = dst.with_file_name(tmp_name);
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| Error::io(Operation::Open, &path, e))?;
+        Ok(TempFile {
+            path,
+            file: Some(file),
+        })
+    }
+
+    /// Copy `reader` into the file while hashing it, `read_error` giving the context of read
+    /// errors.
+    pub(crate) fn write_hashed<R, F>(
+        &mut self,
+        mut reader: R,
+        read_error: F,
+    ) -> Result<Sha256Hash, Error>
+    where
+        R: Read,
+        F: Fn(u64, io::Error) -> Error,
+    {
+        let (path, file) = (&self.path, self.file.as_mut().expect("temp file persisted"));
+        let mut sha = Sha256::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut offset = 0;
+        loop {
+            let n = reader.read(&mut buf).map_err(|e| read_error(offset, e))?;
+            if n == 0 {
+                break;
+            }
+            sha.update(&buf[..n]);
+            file.write_all(&buf[..n])
+                .map_err(|e| Error::io(Operation::Write, path, e))?;
+            offset += n as u64;
+        }
+        Ok(sha.finalize().into())
+    }
+
+    /// Sync the file and rename it to `dst`.
+    pub(crate) fn persist(mut self, dst: &Path) -> Result<(), Error> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()
+                .map_err(|e| Error::io(Operation::Write, &self.path, e))?;
+        }
+        fs::rename(&self.path, dst).map_err(|e| Error::io(Operation::Write, dst, e))?;
+        self.path = PathBuf::new();
+        // Make the rename itself durable.
+        #[cfg(unix)]
+        if let Some(dir) = dst.parent() {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            fs::File::open(dir)
+                .and_then(|dir| dir.sync_all())
+                .map_err(|e| Error::io(Operation::Write, dir, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        self.file = None;
+        if !self.path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::digest2_raw;
+use crate::hash::ct_eq;
+use std::fmt;
+
+/// The Bitcoin base58 alphabet, without `0`, `O`, `I` and `l`.
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Base58Check encode `payload`, appending the first 4 bytes of its double sha256
+///
+/// The payload is taken as is: for Bitcoin addresses and keys it starts with the version byte.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{base58check_decode, base58check_encode};
+/// let encoded = base58check_encode([0x00; 21]);
+/// assert_eq!(encoded, "1111111111111111111114oLvT2");
+/// assert_eq!(base58check_decode(&encoded).unwrap(), [0x00; 21]);
+/// ```
+pub fn base58check_encode<T: AsRef<[u8]>>(payload: T) -> String {
+    let payload = payload.as_ref();
+    let mut data = payload.to_vec();
+    data.extend_from_slice(&digest2_raw(payload)[..4]);
+    encode(&data)
+}
+
+/// Decode Base58Check, returning the payload without its checksum
+pub fn base58check_decode(s: &str) -> Result<Vec<u8>, Base58Error> {
+    let mut data = decode(s)?;
+    if data.len() < 4 {
+        return Err(Base58Error::TooShort);
+    }
+    let checksum = data.split_off(data.len() - 4);
+    if !ct_eq(&digest2_raw(&data)[..4], &checksum) {
+        return Err(Base58Error::Checksum);
+    }
+    Ok(data)
+}
+
+/// Why [`base58check_decode`] failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Error {
+    /// A character outside of the base58 alphabet, at this byte index.
+    InvalidCharacter(usize),
+    /// Less than the 4 bytes of the checksum.
+    TooShort,
+    /// The checksum doesn't match the payload.
+    Checksum,
+}
+
+impl fmt::Display for Base58Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base58Error::InvalidCharacter(i) => write!(f, "invalid base58 character at {}", i),
+            Base58Error::TooShort => f.write_str("base58check data shorter than its checksum"),
+            Base58Error::Checksum => f.write_str("base58check checksum mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for Base58Error {}
+
+fn encode(data: &[u8]) -> String {
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    // Base 58 digits, least significant first.
+    let mut digits: Vec<u8> = Vec::with_capacity(data.len() * 138 / 100 + 1);
+    for &byte in &data[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut out = String::with_capacity(zeros + digits.len());
+    out.extend(std::iter::repeat_n('1', zeros));
+    out.extend(digits.iter().rev().map(|&d| ALPHABET[d as usize] as char));
+    out
+}
+
+fn decode(s: &str) -> Result<Vec<u8>, Base58Error> {
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    // Bytes, least significant first.
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 733 / 1000 + 1);
+    for (i, c) in s.bytes().enumerate().skip(zeros) {
+        let mut carry = match ALPHABET.iter().position(|&a| a == c) {
+            Some(value) => value as u32,
+            None => return Err(Base58Error::InvalidCharacter(i)),
+        };
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+    Ok(bytes)
+}
@@ -0,0 +1,88 @@
+//! Measure how fast this machine hashes
+//!
+//! ```rust
+//! use sha256::bench::{measure, Backend};
+//! let report = measure(Backend::Sha2, 64 * 1024);
+//! println!("{:.0} MiB/s", report.mib_per_sec());
+//! ```
+
+use crate::CalculatorSelector;
+use sha2::{Digest, Sha256};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Hash messages for at least this long, so that timer resolution doesn't matter.
+const MIN_DURATION: Duration = Duration::from_millis(100);
+
+/// The SHA-256 implementation to measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// The `sha2` crate, used by everything but the `openssl` functions.
+    Sha2,
+    /// OpenSSL, used by the `openssl` functions.
+    #[cfg(feature = "native_openssl")]
+    OpenSsl,
+}
+
+/// How fast a [`Backend`] hashed messages of `message_size` bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThroughputReport {
+    pub backend: Backend,
+    pub message_size: usize,
+    /// Number of messages hashed.
+    pub messages: u64,
+    pub elapsed: Duration,
+}
+
+impl ThroughputReport {
+    /// Total number of bytes hashed.
+    pub fn bytes(&self) -> u64 {
+        self.messages * self.message_size as u64
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        self.bytes() as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn mib_per_sec(&self) -> f64 {
+        self.bytes_per_sec() / (1 << 20) as f64
+    }
+
+    pub fn messages_per_sec(&self) -> f64 {
+        self.messages as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Hash messages of `size` bytes with `backend` for about 100 ms and report the throughput
+pub fn measure(backend: Backend, size: usize) -> ThroughputReport {
+    let message = vec![0x5a; size];
+    match backend {
+        Backend::Sha2 => run(backend, &message, Sha256::new),
+        #[cfg(feature = "native_openssl")]
+        Backend::OpenSsl => run(backend, &message, crate::openssl_sha256::OpenSslSha256::new),
+    }
+}
+
+fn run<S, F>(backend: Backend, message: &[u8], new: F) -> ThroughputReport
+where
+    S: CalculatorSelector,
+    F: Fn() -> S,
+{
+    let start = Instant::now();
+    let mut messages = 0;
+    loop {
+        let mut sha = new();
+        sha.update_inner(black_box(message));
+        black_box(sha.finish_inner());
+        messages += 1;
+        let elapsed = start.elapsed();
+        if elapsed >= MIN_DURATION {
+            return ThroughputReport {
+                backend,
+                message_size: message.len(),
+                messages,
+                elapsed,
+            };
+        }
+    }
+}
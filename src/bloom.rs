@@ -0,0 +1,142 @@
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+
+/// Most hash functions of a [`BloomFilter`], one per 32-bit slice of a sha256.
+const MAX_HASHES: u32 = 8;
+
+/// A Bloom filter taking its bit indexes from the sha256 of each item
+///
+/// The digest is cut into eight 32-bit big endian slices, and the first `hashes` of them,
+/// modulo the number of bits, are the bits of the item. Items whose digest is already known
+/// can be added and looked up by digest, without hashing them again.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::BloomFilter;
+/// let mut filter = BloomFilter::with_rate(1000, 0.01);
+/// filter.insert("hello");
+/// assert!(filter.contains("hello"));
+/// let restored = BloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+/// assert!(restored.contains("hello"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u8>,
+    nbits: u64,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// An empty filter of `bits` bits, setting `hashes` bits per item.
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is 0 or over 2<sup>32</sup>, or `hashes` isn't between 1 and 8.
+    pub fn new(bits: u64, hashes: u32) -> Self {
+        assert!(
+            bits > 0 && bits <= 1 << 32,
+            "bits must be between 1 and 2^32"
+        );
+        assert!(
+            (1..=MAX_HASHES).contains(&hashes),
+            "hashes must be between 1 and 8"
+        );
+        BloomFilter {
+            bits: vec![0; bits.div_ceil(8) as usize],
+            nbits: bits,
+            hashes,
+        }
+    }
+
+    /// An empty filter sized for `items` items with a `false_positive_rate` chance of false
+    /// positives, as long as it fits in 2<sup>32</sup> bits and 8 hashes.
+    ///
+    /// # Panics
+    ///
+    /// If `false_positive_rate` isn't strictly between 0 and 1.
+    pub fn with_rate(items: usize, false_positive_rate: f64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false_positive_rate must be between 0 and 1"
+        );
+        let ln2 = std::f64::consts::LN_2;
+        let bits = (-(items.max(1) as f64) * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let bits = (bits as u64).clamp(1, 1 << 32);
+        let hashes = ((bits as f64 / items.max(1) as f64) * ln2).round() as u32;
+        Self::new(bits, hashes.clamp(1, MAX_HASHES))
+    }
+
+    pub fn insert<T: AsRef<[u8]>>(&mut self, item: T) {
+        self.insert_hash(&Sha256::digest(item).into())
+    }
+
+    /// Add the item whose sha256 is `hash`.
+    pub fn insert_hash(&mut self, hash: &Sha256Hash) {
+        for bit in self.indexes(hash) {
+            self.bits[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Whether `item` may have been added, with no false negatives.
+    pub fn contains<T: AsRef<[u8]>>(&self, item: T) -> bool {
+        self.contains_hash(&Sha256::digest(item).into())
+    }
+
+    /// Whether the item whose sha256 is `hash` may have been added.
+    pub fn contains_hash(&self, hash: &Sha256Hash) -> bool {
+        self.indexes(hash)
+            .all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    /// Number of bits of the filter.
+    pub fn bits(&self) -> u64 {
+        self.nbits
+    }
+
+    /// Number of bits set per item.
+    pub fn hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    /// The number of bits as a 64-bit big endian integer, the number of hashes as a byte, then
+    /// the bits.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(9 + self.bits.len());
+        bytes.extend_from_slice(&self.bits().to_be_bytes());
+        bytes.push(self.hashes as u8);
+        bytes.extend_from_slice(&self.bits);
+        bytes
+    }
+
+    /// Read back what [`to_bytes`](BloomFilter::to_bytes) wrote, `None` if it isn't valid.
+    pub fn from_bytes(bytes: &[u8]) -> Option<BloomFilter> {
+        if bytes.len() < 9 {
+            return None;
+        }
+        let bits = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+        let hashes = bytes[8] as u32;
+        if bits == 0 || bits > 1 << 32 || !(1..=MAX_HASHES).contains(&hashes) {
+            return None;
+        }
+        if bytes.len() - 9 != bits.div_ceil(8) as usize {
+            return None;
+        }
+        Some(BloomFilter {
+            bits: bytes[9..].to_vec(),
+            nbits: bits,
+            hashes,
+        })
+    }
+
+    fn indexes<'a>(&self, hash: &'a Sha256Hash) -> impl Iterator<Item = usize> + 'a {
+        let bits = self.bits();
+        hash.as_bytes()
+            .chunks(4)
+            .take(self.hashes as usize)
+            .map(move |slice| {
+                (u32::from_be_bytes(slice.try_into().unwrap()) as u64 % bits) as usize
+            })
+    }
+}
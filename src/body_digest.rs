@@ -0,0 +1,108 @@
+use crate::lower_hex;
+use bytes::{Buf, Bytes};
+use http_body::{Body, Frame, SizeHint};
+use sha2::{Digest, Sha256};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+
+pin_project_lite::pin_project! {
+    /// `http_body::Body` wrapper hashing every data frame passing through it
+    ///
+    /// Trailers are forwarded untouched and are not part of the digest. The digest is available
+    /// from the [`DigestHandle`] once the inner body has reached its end.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sha256::DigestBody;
+    /// use http_body_util::{BodyExt, Full};
+    /// tokio_test::block_on(async{
+    /// let (body, handle) = DigestBody::new(Full::new(bytes::Bytes::from("hello")));
+    /// let data = body.collect().await.unwrap().to_bytes();
+    /// assert_eq!(data, "hello");
+    /// assert_eq!(handle.digest().unwrap(),"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+    /// });
+    /// ```
+    #[derive(Debug)]
+    pub struct DigestBody<B> {
+        #[pin]
+        inner: B,
+        sha: Option<Sha256>,
+        handle: DigestHandle,
+    }
+}
+
+/// handle to the digest computed by a [`DigestBody`]
+#[derive(Debug, Clone, Default)]
+pub struct DigestHandle(Arc<Mutex<Option<String>>>);
+
+impl DigestHandle {
+    /// the hex digest, or `None` while the body hasn't been fully read
+    pub fn digest(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl<B> DigestBody<B> {
+    pub fn new(inner: B) -> (Self, DigestHandle) {
+        let handle = DigestHandle::default();
+        let body = DigestBody {
+            inner,
+            sha: Some(Sha256::new()),
+            handle: handle.clone(),
+        };
+        (body, handle)
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+fn finish(sha: &mut Option<Sha256>, handle: &DigestHandle) {
+    if let Some(sha) = sha.take() {
+        *handle.0.lock().unwrap() = Some(lower_hex::encode(sha.finalize()));
+    }
+}
+
+impl<B: Body> Body for DigestBody<B> {
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let frame = match ready!(this.inner.as_mut().poll_frame(cx)) {
+            Some(Ok(frame)) => frame,
+            Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+            None => {
+                finish(this.sha, this.handle);
+                return Poll::Ready(None);
+            }
+        };
+        let sha = &mut *this.sha;
+        let frame = frame.map_data(|mut data| {
+            let bytes = data.copy_to_bytes(data.remaining());
+            if let Some(sha) = sha {
+                sha.update(&bytes);
+            }
+            bytes
+        });
+        // Callers may stop polling as soon as the body reports its end.
+        if this.inner.is_end_stream() {
+            finish(this.sha, this.handle);
+        }
+        Poll::Ready(Some(Ok(frame)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{calc_file, open_file, Error, Operation, Sha256Hash};
+use std::collections::HashMap;
+use std::fs;
+#[cfg(feature = "persistent_cache")]
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+#[cfg(feature = "persistent_cache")]
+use std::time::{Duration, UNIX_EPOCH};
+
+/// In-memory cache of file digests
+///
+/// Digests are keyed by canonical path and remembered along with the file's size and
+/// modification time; a file whose size or mtime changed is hashed again.
+///
+/// With the `persistent_cache` feature, [`DigestCache::open`] backs the cache with an
+/// append-only file so it survives process restarts.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::DigestCache;
+/// let cache = DigestCache::new();
+/// let val = cache.digest("./foo.file").unwrap();
+/// assert_eq!(val.to_hex(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// assert_eq!(cache.digest("./foo.file").unwrap(), val);
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct DigestCache {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<PathBuf, CacheEntry>,
+    #[cfg(feature = "persistent_cache")]
+    log: Option<(PathBuf, fs::File)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CacheEntry {
+    pub(crate) len: u64,
+    pub(crate) mtime: Option<SystemTime>,
+    pub(crate) hash: Sha256Hash,
+}
+
+impl DigestCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the cache persisted at `path`, creating the file if needed
+    ///
+    /// Every digest the cache computes from now on is appended to the file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sha256::DigestCache;
+    /// let path = std::env::temp_dir().join("sha256_doc_digest_cache");
+    /// let cache = DigestCache::open(&path).unwrap();
+    /// let val = cache.digest("./foo.file").unwrap();
+    /// drop(cache);
+    ///
+    /// let cache = DigestCache::open(&path).unwrap();
+    /// assert!(!cache.is_empty());
+    /// assert_eq!(cache.digest("./foo.file").unwrap(), val);
+    /// ```
+    #[cfg(feature = "persistent_cache")]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let log = fs::OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| Error::io(Operation::Open, path, e))?;
+        let mut entries = HashMap::new();
+        for line in BufReader::new(&log).split(b'\n') {
+            let line = line.map_err(|e| Error::io(Operation::Read, path, e))?;
+            // A line cut short by a crash is just skipped.
+            if let Some((key, entry)) = decode_entry(&line) {
+                entries.insert(key, entry);
+            }
+        }
+        let inner = Inner {
+            entries,
+            log: Some((path.to_path_buf(), log)),
+        };
+        Ok(DigestCache {
+            inner: Mutex::new(inner),
+        })
+    }
+
+    /// The digest of the file at `path`, from the cache if the file didn't change.
+    pub fn digest<P: AsRef<Path>>(&self, path: P) -> Result<Sha256Hash, Error> {
+        let path = path.as_ref();
+        let key = fs::canonicalize(path).map_err(|e| Error::io(Operation::Metadata, path, e))?;
+        let f = open_file(path)?;
+        let meta = f
+            .metadata()
+            .map_err(|e| Error::io(Operation::Metadata, path, e))?;
+        let (len, mtime) = (meta.len(), meta.modified().ok());
+        if let Some(entry) = self.inner.lock().unwrap().entries.get(&key) {
+            if entry.len == len && entry.mtime.is_some() && entry.mtime == mtime {
+                return Ok(entry.hash);
+            }
+        }
+        let hash = calc_file(path, f)?.into();
+        let entry = CacheEntry { len, mtime, hash };
+        let mut inner = self.inner.lock().unwrap();
+        #[cfg(feature = "persistent_cache")]
+        if let Some((log_path, log)) = &mut inner.log {
+            log.write_all(&encode_entry(&key, &entry))
+                .map_err(|e| Error::io(Operation::Write, log_path, e))?;
+        }
+        inner.entries.insert(key, entry);
+        Ok(hash)
+    }
+
+    /// Rewrite the cache file with only the current entries, dropping stale ones.
+    #[cfg(feature = "persistent_cache")]
+    pub fn compact(&self) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        let path = match &inner.log {
+            Some((path, _)) => path.clone(),
+            None => return Ok(()),
+        };
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let mut buf = vec![];
+        for (key, entry) in &inner.entries {
+            buf.extend(encode_entry(key, entry));
+        }
+        fs::write(&tmp, buf).map_err(|e| Error::io(Operation::Write, &tmp, e))?;
+        fs::rename(&tmp, &path).map_err(|e| Error::io(Operation::Write, &path, e))?;
+        let log = fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map_err(|e| Error::io(Operation::Open, &path, e))?;
+        inner.log = Some((path, log));
+        Ok(())
+    }
+
+    /// Forget the digest of `path`, returning whether there was one.
+    pub fn remove<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.inner.lock().unwrap().entries.remove(&key).is_some()
+    }
+
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One line of the cache file: `<digest> <size> <mtime secs.nanos or -> <hex of path bytes>`.
+#[cfg(feature = "persistent_cache")]
+fn encode_entry(key: &Path, entry: &CacheEntry) -> Vec<u8> {
+    let mtime = match entry.mtime.map(|mtime| mtime.duration_since(UNIX_EPOCH)) {
+        Some(Ok(d)) => format!("{}.{:09}", d.as_secs(), d.subsec_nanos()),
+        _ => "-".to_string(),
+    };
+    let key = crate::lower_hex::encode(crate::dir::encode_rel_path(key));
+    format!("{} {} {} {}\n", entry.hash, entry.len, mtime, key).into_bytes()
+}
+
+#[cfg(feature = "persistent_cache")]
+fn decode_entry(line: &[u8]) -> Option<(PathBuf, CacheEntry)> {
+    let line = std::str::from_utf8(line).ok()?;
+    let mut fields = line.split(' ');
+    let hash = fields.next()?.parse().ok()?;
+    let len = fields.next()?.parse().ok()?;
+    let mtime = match fields.next()? {
+        "-" => None,
+        mtime => {
+            let (secs, nanos) = mtime.split_once('.')?;
+            let d = Duration::new(secs.parse().ok()?, nanos.parse().ok()?);
+            Some(UNIX_EPOCH + d)
+        }
+    };
+    let key = decode_path(hex::decode(fields.next()?).ok()?)?;
+    if fields.next().is_some() {
+        return None;
+    }
+    Some((key, CacheEntry { len, mtime, hash }))
+}
+
+#[cfg(all(feature = "persistent_cache", unix))]
+fn decode_path(bytes: Vec<u8>) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+
+    Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+}
+
+#[cfg(all(feature = "persistent_cache", not(unix)))]
+fn decode_path(bytes: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
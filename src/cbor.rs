@@ -0,0 +1,507 @@
+use crate::lower_hex;
+use serde::ser::{self, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+
+/// sha256 digest the deterministic CBOR encoding of `value`, as hex
+///
+/// The value is encoded following the core deterministic encoding requirements of
+/// [RFC 8949](https://www.rfc-editor.org/rfc/rfc8949#section-4.2.1): shortest integer and
+/// length heads, definite lengths only, map entries sorted by the bytes of their encoded keys,
+/// and floats in the shortest of half, single or double precision that keeps their value.
+///
+/// Types map to CBOR as they do with `ciborium`: structs and maps are maps, sequences and
+/// tuples arrays, `None` and `()` null, and enum variants with data single-entry maps from the
+/// variant name. Integers must fit in 64 bits (plus sign).
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, digest_cbor};
+/// use std::collections::HashMap;
+/// let value = HashMap::from([("b", 1), ("a", 2)]);
+/// // {"a": 2, "b": 1}
+/// assert_eq!(digest_cbor(&value).unwrap(), digest(&[0xa2, 0x61, b'a', 0x02, 0x61, b'b', 0x01][..]));
+/// ```
+pub fn digest_cbor<T: Serialize + ?Sized>(value: &T) -> Result<String, CborError> {
+    let cbor = to_canonical_cbor(value)?;
+    Ok(lower_hex::encode(Sha256::digest(cbor)))
+}
+
+/// The deterministic CBOR encoding of `value`, what [`digest_cbor`] hashes
+pub fn to_canonical_cbor<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, CborError> {
+    let mut out = Vec::new();
+    value.serialize(Cbor { out: &mut out })?;
+    Ok(out)
+}
+
+/// Why a value has no deterministic CBOR encoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CborError {
+    /// An integer beyond the 64 bits of CBOR integers.
+    IntegerOutOfRange,
+    /// An error of the `Serialize` implementation.
+    Custom(String),
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::IntegerOutOfRange => f.write_str("integer outside of the CBOR range"),
+            CborError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for CborError {}
+
+impl ser::Error for CborError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        CborError::Custom(msg.to_string())
+    }
+}
+
+/// The initial bytes of an item of major type `major`, with the shortest argument encoding.
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        out.push(major | arg as u8);
+    } else if arg <= u8::MAX as u64 {
+        out.extend_from_slice(&[major | 24, arg as u8]);
+    } else if arg <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+fn write_text(out: &mut Vec<u8>, s: &str) {
+    write_head(out, TEXT, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+/// A float in the shortest of half, single and double precision that keeps its value.
+fn write_float(out: &mut Vec<u8>, v: f64) {
+    if v.is_nan() {
+        out.extend_from_slice(&[0xf9, 0x7e, 0x00]);
+        return;
+    }
+    let single = v as f32;
+    if single as f64 != v {
+        out.push(0xfb);
+        out.extend_from_slice(&v.to_be_bytes());
+    } else if let Some(half) = to_half(single) {
+        out.push(0xf9);
+        out.extend_from_slice(&half.to_be_bytes());
+    } else {
+        out.push(0xfa);
+        out.extend_from_slice(&single.to_be_bytes());
+    }
+}
+
+/// The bits of `v` as a half precision float, `None` if it's not exactly representable.
+fn to_half(v: f32) -> Option<u16> {
+    let bits = v.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    match exponent {
+        0 if mantissa == 0 => Some(sign),
+        0 => None,
+        // Infinities, NaNs are handled before.
+        255 => Some(sign | 0x7c00),
+        _ => {
+            let e = exponent - 127;
+            if (-14..=15).contains(&e) {
+                (mantissa & 0x1fff == 0)
+                    .then(|| sign | ((e + 15) as u16) << 10 | (mantissa >> 13) as u16)
+            } else if (-24..-14).contains(&e) {
+                // Subnormal half: the value is (1.mantissa * 2^e) = h * 2^-24.
+                let significand = mantissa | 0x80_0000;
+                let shift = -(e + 1);
+                (significand & ((1 << shift) - 1) == 0)
+                    .then(|| sign | (significand >> shift) as u16)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+struct Cbor<'a> {
+    out: &'a mut Vec<u8>,
+}
+
+impl Cbor<'_> {
+    fn integer(self, negative: bool, magnitude: u128) -> Result<(), CborError> {
+        match (negative, magnitude) {
+            (false, m) if m <= u64::MAX as u128 => write_head(self.out, UNSIGNED, m as u64),
+            (true, m) if m >= 1 && m - 1 <= u64::MAX as u128 => {
+                write_head(self.out, NEGATIVE, (m - 1) as u64)
+            }
+            _ => return Err(CborError::IntegerOutOfRange),
+        }
+        Ok(())
+    }
+
+    /// A map with the single key `variant`, whose value is written next.
+    fn open_variant(&mut self, variant: &str) {
+        write_head(self.out, MAP, 1);
+        write_text(self.out, variant);
+    }
+}
+
+impl<'a> ser::Serializer for Cbor<'a> {
+    type Ok = ();
+    type Error = CborError;
+    type SerializeSeq = Seq<'a>;
+    type SerializeTuple = Seq<'a>;
+    type SerializeTupleStruct = Seq<'a>;
+    type SerializeTupleVariant = Seq<'a>;
+    type SerializeMap = Map<'a>;
+    type SerializeStruct = Map<'a>;
+    type SerializeStructVariant = Map<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), CborError> {
+        self.out.push(if v { 0xf5 } else { 0xf4 });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CborError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CborError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CborError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CborError> {
+        self.integer(v < 0, v.unsigned_abs() as u128)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), CborError> {
+        self.integer(v < 0, v.unsigned_abs())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CborError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CborError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CborError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CborError> {
+        write_head(self.out, UNSIGNED, v);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), CborError> {
+        self.integer(false, v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), CborError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), CborError> {
+        write_float(self.out, v);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CborError> {
+        write_text(self.out, v.encode_utf8(&mut [0; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CborError> {
+        write_text(self.out, v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CborError> {
+        write_head(self.out, BYTES, v.len() as u64);
+        self.out.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), CborError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CborError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CborError> {
+        self.out.push(0xf6);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CborError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), CborError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CborError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        mut self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), CborError> {
+        self.open_variant(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Seq<'a>, CborError> {
+        Ok(Seq {
+            out: self.out,
+            items: Vec::new(),
+            len: 0,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Seq<'a>, CborError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Seq<'a>, CborError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Seq<'a>, CborError> {
+        self.open_variant(variant);
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Map<'a>, CborError> {
+        Ok(Map {
+            out: self.out,
+            entries: Vec::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Map<'a>, CborError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Map<'a>, CborError> {
+        self.open_variant(variant);
+        self.serialize_map(Some(len))
+    }
+}
+
+/// Arrays, buffered to write their definite length first.
+struct Seq<'a> {
+    out: &'a mut Vec<u8>,
+    items: Vec<u8>,
+    len: u64,
+}
+
+impl Seq<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        self.len += 1;
+        value.serialize(Cbor {
+            out: &mut self.items,
+        })
+    }
+
+    fn close(self) -> Result<(), CborError> {
+        write_head(self.out, ARRAY, self.len);
+        self.out.extend_from_slice(&self.items);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for Seq<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTuple for Seq<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleStruct for Seq<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleVariant for Seq<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
+
+/// Maps, whose entries are collected and written sorted by encoded key on `end`.
+struct Map<'a> {
+    out: &'a mut Vec<u8>,
+    /// Encoded keys and values.
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    key: Option<Vec<u8>>,
+}
+
+impl Map<'_> {
+    fn value<T: Serialize + ?Sized>(&mut self, key: Vec<u8>, value: &T) -> Result<(), CborError> {
+        let mut encoded = Vec::new();
+        value.serialize(Cbor { out: &mut encoded })?;
+        self.entries.push((key, encoded));
+        Ok(())
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), CborError> {
+        let mut encoded = Vec::new();
+        write_text(&mut encoded, key);
+        self.value(encoded, value)
+    }
+
+    fn close(mut self) -> Result<(), CborError> {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        write_head(self.out, MAP, self.entries.len() as u64);
+        for (key, value) in &self.entries {
+            self.out.extend_from_slice(key);
+            self.out.extend_from_slice(value);
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Map<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CborError> {
+        let mut encoded = Vec::new();
+        key.serialize(Cbor { out: &mut encoded })?;
+        self.key = Some(encoded);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.value(key, value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStruct for Map<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CborError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStructVariant for Map<'_> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), CborError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        self.close()
+    }
+}
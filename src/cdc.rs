@@ -0,0 +1,113 @@
+use crate::{open_file, Error, Sha256Hash};
+use fastcdc::v2020::StreamCDC;
+use sha2::{Digest, Sha256};
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+/// Chunk sizes for [`try_digest_cdc`]
+///
+/// Sizes must be within the bounds of FastCDC: `min_size` in 64 B..=1 MiB, `avg_size` in
+/// 256 B..=4 MiB and `max_size` in 1 KiB..=16 MiB.
+#[derive(Debug, Clone)]
+pub struct CdcOptions {
+    min_size: u32,
+    avg_size: u32,
+    max_size: u32,
+}
+
+impl Default for CdcOptions {
+    fn default() -> Self {
+        CdcOptions {
+            min_size: 16 * 1024,
+            avg_size: 64 * 1024,
+            max_size: 256 * 1024,
+        }
+    }
+}
+
+impl CdcOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Smallest chunk but the last one, 16 KiB by default.
+    pub fn min_size(mut self, min_size: u32) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Chunk size aimed for, 64 KiB by default.
+    pub fn avg_size(mut self, avg_size: u32) -> Self {
+        self.avg_size = avg_size;
+        self
+    }
+
+    /// Largest chunk, 256 KiB by default.
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+}
+
+/// A content defined chunk of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// Byte range of the chunk in the file.
+    pub range: Range<u64>,
+    pub hash: Sha256Hash,
+}
+
+/// Result of [`try_digest_cdc`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdcDigest {
+    pub chunks: Vec<Chunk>,
+    /// Digest of the whole file.
+    pub hash: Sha256Hash,
+}
+
+/// Split a file into FastCDC chunks, hashing every chunk and the whole file in one pass
+///
+/// Chunk boundaries depend on the content only, so an insertion near the start of a file shifts
+/// the chunks after it without changing their digests.
+///
+/// # Panics
+///
+/// If the sizes of `options` are out of FastCDC's bounds.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{try_digest_cdc, CdcOptions};
+/// let res = try_digest_cdc("./foo.file", &CdcOptions::new()).unwrap();
+/// assert_eq!(res.chunks.len(), 1);
+/// assert_eq!(res.chunks[0].range, 0..12);
+/// assert_eq!(res.hash.to_hex(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// ```
+pub fn try_digest_cdc<P: AsRef<Path>>(path: P, options: &CdcOptions) -> Result<CdcDigest, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    let chunker = StreamCDC::new(f, options.min_size, options.avg_size, options.max_size);
+    let mut whole = Sha256::new();
+    let mut chunks = vec![];
+    let mut offset = 0;
+    for chunk in chunker {
+        let chunk = chunk.map_err(|e| {
+            let e = match e {
+                fastcdc::v2020::Error::IoError(e) => e,
+                e => io::Error::other(e.to_string()),
+            };
+            Error::read(path, offset, e)
+        })?;
+        whole.update(&chunk.data);
+        offset = chunk.offset + chunk.length as u64;
+        chunks.push(Chunk {
+            range: chunk.offset..offset,
+            hash: Sha256::digest(&chunk.data).into(),
+        });
+    }
+    Ok(CdcDigest {
+        chunks,
+        hash: whole.finalize().into(),
+    })
+}
@@ -0,0 +1,129 @@
+//! Tamper-evident hash chains
+//!
+//! Each link commits to the one before it, so changing, removing or reordering any record
+//! changes every digest after it.
+//!
+//! ```rust
+//! use sha256::chain::HashChain;
+//! let mut chain = HashChain::new();
+//! chain.append("alice logged in");
+//! chain.append("alice deleted bob");
+//! let saved = chain.to_string();
+//! let loaded = HashChain::parse(saved.as_bytes()).unwrap();
+//! assert_eq!(loaded.verify(), Ok(()));
+//! assert_eq!(loaded.head(), chain.head());
+//! ```
+
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{self, Write};
+
+/// A sequence of records, each linked to the previous one by its digest
+///
+/// Link `i` has the digest `sha256(digest of link i-1 || data)`, the first one starting from 32
+/// zero bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HashChain {
+    links: Vec<Link>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub data: Vec<u8>,
+    pub hash: Sha256Hash,
+}
+
+impl HashChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// The digest of the last link, all zeros for an empty chain.
+    pub fn head(&self) -> Sha256Hash {
+        self.links.last().map(|link| link.hash).unwrap_or_default()
+    }
+
+    /// Add a record, returning the digest of its link.
+    pub fn append<T: Into<Vec<u8>>>(&mut self, data: T) -> Sha256Hash {
+        let data = data.into();
+        let hash = link_hash(&self.head(), &data);
+        self.links.push(Link { data, hash });
+        hash
+    }
+
+    /// Recompute the chain from the start, failing with the index of the first link whose
+    /// digest doesn't match.
+    pub fn verify(&self) -> Result<(), usize> {
+        let mut prev = Sha256Hash::default();
+        for (i, link) in self.links.iter().enumerate() {
+            if link_hash(&prev, &link.data) != link.hash {
+                return Err(i);
+            }
+            prev = link.hash;
+        }
+        Ok(())
+    }
+
+    /// Write the chain one link per line, as `<hex digest> <hex data>`.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        write!(w, "{}", self)
+    }
+
+    /// Read back what [`write_to`](HashChain::write_to) wrote, without verifying it.
+    pub fn parse(input: &[u8]) -> Result<HashChain, ParseError> {
+        let mut chain = HashChain::new();
+        for (i, line) in input.split(|&b| b == b'\n').enumerate() {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            let link = parse_line(line).ok_or(ParseError { line: i + 1 })?;
+            chain.links.push(link);
+        }
+        Ok(chain)
+    }
+}
+
+impl fmt::Display for HashChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for link in &self.links {
+            writeln!(f, "{} {}", link.hash, crate::lower_hex::encode(&link.data))?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of a serialized [`HashChain`] that isn't `<hex digest> <hex data>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "improperly formatted hash chain line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_line(line: &[u8]) -> Option<Link> {
+    let line = std::str::from_utf8(line).ok()?;
+    let (hash, data) = line.split_once(' ')?;
+    Some(Link {
+        data: hex::decode(data).ok()?,
+        hash: hash.parse().ok()?,
+    })
+}
+
+fn link_hash(prev: &Sha256Hash, data: &[u8]) -> Sha256Hash {
+    let mut sha = Sha256::new_with_prefix(prev.as_bytes());
+    sha.update(data);
+    sha.finalize().into()
+}
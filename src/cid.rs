@@ -0,0 +1,50 @@
+use crate::{calc_file, open_file, Error, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// CIDv1 prefix: version 1, `raw` codec (0x55), `sha2-256` multihash (0x12) of 32 bytes.
+const CID_V1_RAW_SHA256: [u8; 4] = [0x01, 0x55, 0x12, 0x20];
+
+/// RFC 4648 base32, lowercase, as multibase `b`.
+const BASE32: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+/// IPFS CIDv1 of `input` as a raw block, in the default base32 multibase
+///
+/// This is the CID `ipfs add --cid-version 1 --raw-leaves` gives content that fits in a
+/// single block (256 KiB with the default chunker). Larger content is split into a UnixFS DAG
+/// by IPFS, whose root CID is not computed here.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::cid_v1_raw;
+/// let cid = cid_v1_raw("hello world");
+/// assert_eq!(cid,"bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e");
+/// ```
+pub fn cid_v1_raw<T: AsRef<[u8]>>(input: T) -> String {
+    cid_v1_raw_from_hash(&Sha256::digest(input).into())
+}
+
+/// IPFS CIDv1 of the contents of the file at `path` as a raw block, see [`cid_v1_raw`]
+pub fn try_cid_v1_raw<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let file = open_file(path)?;
+    Ok(cid_v1_raw_from_hash(&calc_file(path, file)?.into()))
+}
+
+/// IPFS CIDv1 of a raw block whose sha256 is `hash`, see [`cid_v1_raw`]
+pub fn cid_v1_raw_from_hash(hash: &Sha256Hash) -> String {
+    let mut bytes = CID_V1_RAW_SHA256.to_vec();
+    bytes.extend_from_slice(hash.as_bytes());
+    let mut out = String::with_capacity(1 + (bytes.len() * 8).div_ceil(5));
+    out.push('b');
+    for chunk in bytes.chunks(5) {
+        let mut block = [0u8; 8];
+        block[3..3 + chunk.len()].copy_from_slice(chunk);
+        let n = u64::from_be_bytes(block);
+        for i in 0..(chunk.len() * 8).div_ceil(5) {
+            out.push(BASE32[(n >> (35 - 5 * i) & 0x1f) as usize] as char);
+        }
+    }
+    out
+}
@@ -0,0 +1,48 @@
+use crate::hash::ct_eq;
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+
+/// A binding, hiding commitment to a value: `sha256(nonce || value)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Commitment(pub Sha256Hash);
+
+/// The secret nonce that opens a [`Commitment`], revealed along with the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opening(pub [u8; 32]);
+
+/// Commit to `value` with a random 32 byte nonce from the OS
+///
+/// Publish the commitment, keep the opening, and reveal both the value and the opening later.
+///
+/// # Panics
+///
+/// If the OS random number generator fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{commit, verify_commit};
+/// let (commitment, opening) = commit("rock");
+/// assert!(verify_commit(&commitment, "rock", &opening));
+/// assert!(!verify_commit(&commitment, "paper", &opening));
+/// ```
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn commit<T: AsRef<[u8]>>(value: T) -> (Commitment, Opening) {
+    let mut nonce = [0u8; 32];
+    getrandom::fill(&mut nonce).expect("failed to get random bytes from the OS");
+    let opening = Opening(nonce);
+    (commit_with_opening(value, &opening), opening)
+}
+
+/// The commitment to `value` with a given opening, for nonces from another source
+pub fn commit_with_opening<T: AsRef<[u8]>>(value: T, opening: &Opening) -> Commitment {
+    let mut sha = Sha256::new_with_prefix(opening.0);
+    sha.update(value);
+    Commitment(sha.finalize().into())
+}
+
+/// Whether `value` and `opening` open `commitment`, comparing in constant time
+pub fn verify_commit<T: AsRef<[u8]>>(commitment: &Commitment, value: T, opening: &Opening) -> bool {
+    let actual = commit_with_opening(value, opening);
+    ct_eq(actual.0.as_bytes(), commitment.0.as_bytes())
+}
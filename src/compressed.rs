@@ -0,0 +1,56 @@
+use crate::{calc_path, lower_hex, open_file, Error};
+use sha2::{Digest, Sha256};
+use std::io::BufReader;
+use std::path::Path;
+
+/// sha256 digest the decompressed content of a gzip file
+///
+/// Files made of several gzip members are decompressed as a whole, like `gzip -d` does.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, try_digest_gz};
+/// use std::io::Write;
+/// let path = std::env::temp_dir().join("sha256_doc_try_digest_gz.gz");
+/// let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+/// encoder.write_all(b"hello").unwrap();
+/// std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+/// assert_eq!(try_digest_gz(&path).unwrap(), digest("hello"));
+/// ```
+#[cfg(feature = "gzip")]
+pub fn try_digest_gz<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    let decoder = flate2::bufread::MultiGzDecoder::new(BufReader::new(f));
+    Ok(lower_hex::encode(calc_path(
+        path,
+        0,
+        decoder,
+        Sha256::new(),
+    )?))
+}
+
+/// sha256 digest the decompressed content of a zstd file
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, try_digest_zst};
+/// let path = std::env::temp_dir().join("sha256_doc_try_digest_zst.zst");
+/// std::fs::write(&path, zstd::encode_all(&b"hello"[..], 0).unwrap()).unwrap();
+/// assert_eq!(try_digest_zst(&path).unwrap(), digest("hello"));
+/// ```
+#[cfg(feature = "zstd")]
+pub fn try_digest_zst<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    let decoder = zstd::stream::read::Decoder::with_buffer(BufReader::new(f))
+        .map_err(|e| Error::read(path, 0, e))?;
+    Ok(lower_hex::encode(calc_path(
+        path,
+        0,
+        decoder,
+        Sha256::new(),
+    )?))
+}
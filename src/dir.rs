@@ -0,0 +1,677 @@
+use crate::glob::Glob;
+use crate::throttle::{Throttle, Throttled};
+use crate::{calc_file, calc_raw_offset, open_file};
+use crate::{lower_hex, Error, Operation, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+/// Domain tag starting every version 1 directory digest.
+const DIR_V1_TAG: &[u8] = b"sha256-rs dir v1\0";
+/// Domain tag of version 1 directory digests when file names don't participate.
+const DIR_CONTENTS_V1_TAG: &[u8] = b"sha256-rs dir-contents v1\0";
+/// Domain tag of version 1 directory digests including metadata.
+const DIR_META_V1_TAG: &[u8] = b"sha256-rs dir-meta v1\0";
+
+/// sha256 digest a directory tree into a single value
+///
+/// The digest only changes when a file under `path` is added, removed, renamed or modified.
+///
+/// # Encoding (version 1)
+///
+/// Regular files (and symlinks to files) are visited in the order of their relative path
+/// components, symlinks to directories are not followed and empty directories don't contribute.
+/// The digest is the sha256 of:
+///
+/// ```text
+/// "sha256-rs dir v1\0"
+/// for each file:
+///     len(relative path) as u64 big endian
+///     relative path, components joined by '/'
+///     sha256(file contents), 32 raw bytes
+/// ```
+///
+/// Relative paths are the raw bytes on unix and UTF-8 (lossy) elsewhere. Any change to this
+/// encoding will come with a new version tag.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_digest_dir;
+/// let val = try_digest_dir("./src").unwrap();
+/// assert_eq!(val.len(), 64);
+/// ```
+pub fn try_digest_dir<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    try_digest_dir_with(path, &DirOptions::new())
+}
+
+/// sha256 digest a directory tree, filtered and encoded according to `options`
+///
+/// With the default options this is [`try_digest_dir`]. When
+/// [`DirOptions::hash_file_names`] is off, the tag becomes `"sha256-rs dir-contents v1\0"`,
+/// followed by the sorted file content digests alone, so that the digest only depends on the
+/// set of file contents.
+///
+/// When any metadata is selected ([`DirOptions::hash_mode`], [`DirOptions::hash_symlinks`] or
+/// [`DirOptions::hash_mtime`]) the digest is the sha256 of:
+///
+/// ```text
+/// "sha256-rs dir-meta v1\0"
+/// flags: u8, bit 0 mode, bit 1 symlinks, bit 2 mtime
+/// for each entry:
+///     len(relative path) as u64 big endian, relative path   (unless file names are off)
+///     'f' then sha256(file contents), 32 raw bytes
+///     or 'l' then len(target) as u64 big endian, raw symlink target
+///     mode & 0o7777 as u32 big endian                       (if mode)
+///     mtime seconds since the unix epoch, rounded down, as i64 big endian,
+///     then the nanoseconds past them as u32 big endian      (if mtime)
+/// ```
+///
+/// With file names off the entries are sorted by their encoding. Outside of unix the mode is
+/// `0o444` for read-only files and `0o644` for the others.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{try_digest_dir_with, DirOptions};
+/// let options = DirOptions::new().include("*.rs").exclude("tests.rs");
+/// let val = try_digest_dir_with("./src", &options).unwrap();
+/// assert_eq!(val.len(), 64);
+/// ```
+pub fn try_digest_dir_with<P: AsRef<Path>>(path: P, options: &DirOptions) -> Result<String, Error> {
+    let mut records = vec![];
+    let mut throttle = Throttle::new(options.rate_limit);
+    for entry in Walk::with_options(path.as_ref(), options.clone()) {
+        records.push(Record::read(entry?, options, &mut throttle)?);
+    }
+    Ok(digest_records(records, options))
+}
+
+/// Like [`try_digest_dir_with`], skipping what can't be read instead of failing
+///
+/// Files and directories that fail to be read are left out of the digest, and their errors are
+/// collected in the report. The digest only equals the one of [`try_digest_dir_with`] when
+/// [`DirReport::is_complete`] is true.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest_dir_report, try_digest_dir, DirOptions};
+/// let report = digest_dir_report("./src", &DirOptions::new());
+/// assert!(report.is_complete());
+/// assert_eq!(report.hash, try_digest_dir("./src").unwrap());
+/// ```
+pub fn digest_dir_report<P: AsRef<Path>>(path: P, options: &DirOptions) -> DirReport {
+    let mut records = vec![];
+    let mut errors = vec![];
+    let mut throttle = Throttle::new(options.rate_limit);
+    for entry in Walk::with_options(path.as_ref(), options.clone()) {
+        match entry.and_then(|entry| Record::read(entry, options, &mut throttle)) {
+            Ok(record) => records.push(record),
+            Err(e) => errors.push(e),
+        }
+    }
+    DirReport {
+        files: records.len(),
+        hash: digest_records(records, options),
+        errors,
+    }
+}
+
+/// Result of [`digest_dir_report`]
+#[derive(Debug)]
+pub struct DirReport {
+    /// Digest of the files that could be read.
+    pub hash: String,
+    /// Number of files in the digest.
+    pub files: usize,
+    /// What couldn't be read, with its path.
+    pub errors: Vec<Error>,
+}
+
+impl DirReport {
+    /// Whether every file was read, i.e. there are no errors.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+fn digest_records(records: Vec<Record>, options: &DirOptions) -> String {
+    let mut sha = Sha256::new();
+    if options.hashes_metadata() {
+        sha.update(DIR_META_V1_TAG);
+        sha.update([options.metadata_flags()]);
+        let mut encoded = records
+            .iter()
+            .map(|record| record.encode(options))
+            .collect::<Vec<_>>();
+        if !options.hash_file_names {
+            encoded.sort();
+        }
+        for record in encoded {
+            sha.update(record);
+        }
+    } else if options.hash_file_names {
+        sha.update(DIR_V1_TAG);
+        for record in records {
+            let rel = encode_rel_path(&record.rel);
+            sha.update((rel.len() as u64).to_be_bytes());
+            sha.update(&rel);
+            sha.update(record.content);
+        }
+    } else {
+        sha.update(DIR_CONTENTS_V1_TAG);
+        let mut hashes = records
+            .into_iter()
+            .map(|record| record.content)
+            .collect::<Vec<_>>();
+        hashes.sort();
+        for hash in hashes {
+            sha.update(hash);
+        }
+    }
+    lower_hex::encode(sha.finalize())
+}
+
+/// One hashed entry of a directory tree.
+struct Record {
+    rel: PathBuf,
+    symlink: bool,
+    /// File content digest, or the raw target of a symlink.
+    content: Vec<u8>,
+    mode: u32,
+    mtime: (i64, u32),
+}
+
+impl Record {
+    fn read(
+        entry: WalkEntry,
+        options: &DirOptions,
+        throttle: &mut Throttle,
+    ) -> Result<Record, Error> {
+        let path = &entry.path;
+        let metadata_error = |e| Error::io(Operation::Metadata, path, e);
+        let (meta, content) = if entry.symlink {
+            let target = read_link(path)?;
+            (fs::symlink_metadata(path).map_err(metadata_error)?, target)
+        } else {
+            let file = open_file(path)?;
+            let meta = file.metadata().map_err(metadata_error)?;
+            let hash = match options.rate_limit {
+                Some(_) => calc_raw_offset(Throttled::new(file, throttle), Sha256::new())
+                    .map(|(hash, _)| hash)
+                    .map_err(|(e, offset)| Error::read(path, offset, e))?,
+                None => calc_file(path, file)?,
+            };
+            (meta, hash.to_vec())
+        };
+        let mtime = if options.hash_mtime {
+            match meta
+                .modified()
+                .map_err(metadata_error)?
+                .duration_since(UNIX_EPOCH)
+            {
+                Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+                // Before the epoch: floor the seconds so that the nanoseconds stay positive.
+                Err(e) => {
+                    let d = e.duration();
+                    match d.subsec_nanos() {
+                        0 => (-(d.as_secs() as i64), 0),
+                        nanos => (-(d.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                    }
+                }
+            }
+        } else {
+            (0, 0)
+        };
+        Ok(Record {
+            rel: entry.rel,
+            symlink: entry.symlink,
+            content,
+            mode: file_mode(&meta),
+            mtime,
+        })
+    }
+
+    fn encode(&self, options: &DirOptions) -> Vec<u8> {
+        let mut out = vec![];
+        if options.hash_file_names {
+            let rel = encode_rel_path(&self.rel);
+            out.extend_from_slice(&(rel.len() as u64).to_be_bytes());
+            out.extend_from_slice(&rel);
+        }
+        if self.symlink {
+            out.push(b'l');
+            out.extend_from_slice(&(self.content.len() as u64).to_be_bytes());
+        } else {
+            out.push(b'f');
+        }
+        out.extend_from_slice(&self.content);
+        if options.hash_mode {
+            out.extend_from_slice(&self.mode.to_be_bytes());
+        }
+        if options.hash_mtime {
+            out.extend_from_slice(&self.mtime.0.to_be_bytes());
+            out.extend_from_slice(&self.mtime.1.to_be_bytes());
+        }
+        out
+    }
+}
+
+/// The encoded target of the symlink at `path`, what stands for its content.
+fn read_link(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read_link(path)
+        .map(|target| encode_rel_path(&target))
+        .map_err(|e| Error::io(Operation::ReadLink, path, e))
+}
+
+#[cfg(unix)]
+pub(crate) fn file_mode(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    meta.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+pub(crate) fn file_mode(meta: &fs::Metadata) -> u32 {
+    if meta.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+/// sha256 digest every file under `root`, one at a time
+///
+/// Files come in the same deterministic order as for [`try_digest_dir`]. A directory that can't
+/// be read yields its own path with the error, and the walk goes on.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::walk_digest;
+/// for (path, hash) in walk_digest("./src") {
+///     println!("{}  {}", hash.unwrap(), path.display());
+/// }
+/// ```
+pub fn walk_digest<P: AsRef<Path>>(root: P) -> WalkDigest {
+    walk_digest_with(root, &DirOptions::new())
+}
+
+/// sha256 digest every file under `root` selected by `options`, one at a time
+///
+/// Only the filters of `options` and [`DirOptions::hash_symlinks`] apply. Files are hashed by
+/// content, and with `hash_symlinks` a symlink by its target path, as it is in
+/// [`try_digest_dir_with`], even when dangling or pointing to a directory.
+pub fn walk_digest_with<P: AsRef<Path>>(root: P, options: &DirOptions) -> WalkDigest {
+    WalkDigest {
+        walk: Walk::with_options(root.as_ref(), options.clone()),
+    }
+}
+
+/// Iterator returned by [`walk_digest`]
+pub struct WalkDigest {
+    walk: Walk,
+}
+
+impl Iterator for WalkDigest {
+    type Item = (PathBuf, Result<Sha256Hash, Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = match self.walk.next()? {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e.path().map(Path::to_path_buf).unwrap_or_default();
+                return Some((path, Err(e)));
+            }
+        };
+        let hash = if entry.symlink {
+            read_link(&entry.path).map(|target| Sha256::digest(target).into())
+        } else {
+            open_file(&entry.path)
+                .and_then(|file| calc_file(&entry.path, file))
+                .map(Sha256Hash::from)
+        };
+        Some((entry.path, hash))
+    }
+}
+
+/// Which files of a directory tree get hashed, and how
+///
+/// Glob patterns support `*`, `?`, `**` and `[...]` classes. A pattern without `/` is matched
+/// against file names at any depth, one with `/` against the whole path relative to the root.
+#[derive(Debug, Clone)]
+pub struct DirOptions {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+    gitignore: bool,
+    skip_hidden: bool,
+    hash_file_names: bool,
+    hash_mode: bool,
+    hash_symlinks: bool,
+    hash_mtime: bool,
+    rate_limit: Option<u64>,
+}
+
+impl Default for DirOptions {
+    fn default() -> Self {
+        DirOptions {
+            include: vec![],
+            exclude: vec![],
+            gitignore: false,
+            skip_hidden: false,
+            hash_file_names: true,
+            hash_mode: false,
+            hash_symlinks: false,
+            hash_mtime: false,
+            rate_limit: None,
+        }
+    }
+}
+
+impl DirOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only hash files matching one of the include patterns. Everything is included by default.
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(Glob::new(pattern));
+        self
+    }
+
+    /// Skip files and directories matching `pattern`.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(Glob::new(pattern));
+        self
+    }
+
+    /// Honor `.gitignore` files found in the tree, and skip `.git` directories.
+    pub fn gitignore(mut self, gitignore: bool) -> Self {
+        self.gitignore = gitignore;
+        self
+    }
+
+    /// Skip files and directories whose name starts with a `.`.
+    pub fn skip_hidden(mut self, skip_hidden: bool) -> Self {
+        self.skip_hidden = skip_hidden;
+        self
+    }
+
+    /// Whether relative paths are part of the digest, on by default.
+    pub fn hash_file_names(mut self, hash_file_names: bool) -> Self {
+        self.hash_file_names = hash_file_names;
+        self
+    }
+
+    /// Fold permission bits into the digest.
+    pub fn hash_mode(mut self, hash_mode: bool) -> Self {
+        self.hash_mode = hash_mode;
+        self
+    }
+
+    /// Hash symlinks as their target path instead of following them.
+    pub fn hash_symlinks(mut self, hash_symlinks: bool) -> Self {
+        self.hash_symlinks = hash_symlinks;
+        self
+    }
+
+    /// Fold modification times into the digest.
+    pub fn hash_mtime(mut self, hash_mtime: bool) -> Self {
+        self.hash_mtime = hash_mtime;
+        self
+    }
+
+    /// Read at most `bytes_per_sec` bytes per second on average over the whole tree, like
+    /// [`Options::rate_limit`](crate::Options::rate_limit).
+    pub fn rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.rate_limit = Some(bytes_per_sec);
+        self
+    }
+
+    fn hashes_metadata(&self) -> bool {
+        self.hash_mode || self.hash_symlinks || self.hash_mtime
+    }
+
+    fn metadata_flags(&self) -> u8 {
+        self.hash_mode as u8 | (self.hash_symlinks as u8) << 1 | (self.hash_mtime as u8) << 2
+    }
+
+    fn skip(&self, entry: &WalkEntry, is_dir: bool) -> bool {
+        let name = match entry.rel.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return false,
+        };
+        if self.skip_hidden && name.starts_with('.') {
+            return true;
+        }
+        if self.gitignore && is_dir && name == ".git" {
+            return true;
+        }
+        let rel = rel_path_str(&entry.rel);
+        if self
+            .exclude
+            .iter()
+            .any(|glob| glob_matches(glob, &name, &rel))
+        {
+            return true;
+        }
+        if !is_dir
+            && !self.include.is_empty()
+            && !self
+                .include
+                .iter()
+                .any(|glob| glob_matches(glob, &name, &rel))
+        {
+            return true;
+        }
+        match &entry.ignore {
+            Some(ignore) if self.gitignore => ignore.is_ignored(&entry.rel, is_dir),
+            _ => false,
+        }
+    }
+}
+
+fn glob_matches(glob: &Glob, name: &str, rel: &str) -> bool {
+    if glob.anchored() {
+        glob.matches(rel)
+    } else {
+        glob.matches(name)
+    }
+}
+
+fn rel_path_str(rel: &Path) -> String {
+    String::from_utf8_lossy(&encode_rel_path(rel)).into_owned()
+}
+
+/// Path bytes with `/` separators, raw on unix and UTF-8 (lossy) elsewhere.
+#[cfg(unix)]
+pub(crate) fn encode_rel_path(rel: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+
+    rel.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+pub(crate) fn encode_rel_path(rel: &Path) -> Vec<u8> {
+    rel.to_string_lossy().replace('\\', "/").into_bytes()
+}
+
+/// Rules of one `.gitignore` file, chained to the ones of its parent directories.
+#[derive(Debug)]
+struct IgnoreRules {
+    parent: Option<Arc<IgnoreRules>>,
+    /// Directory of the `.gitignore`, relative to the walk root.
+    base: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    glob: Glob,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRules {
+    fn load(parent: Option<Arc<IgnoreRules>>, dir: &Path, base: &Path) -> Option<Arc<IgnoreRules>> {
+        let content = match fs::read_to_string(dir.join(".gitignore")) {
+            Ok(content) => content,
+            Err(_) => return parent,
+        };
+        let rules = content.lines().filter_map(IgnoreRule::parse).collect();
+        Some(Arc::new(IgnoreRules {
+            parent,
+            base: base.to_path_buf(),
+            rules,
+        }))
+    }
+
+    fn is_ignored(&self, rel: &Path, is_dir: bool) -> bool {
+        let mut chain = vec![self];
+        while let Some(parent) = &chain[chain.len() - 1].parent {
+            chain.push(parent);
+        }
+        // Deeper files and later lines take precedence, so the last matching rule wins.
+        let mut ignored = false;
+        for rules in chain.into_iter().rev() {
+            let rel = match rel.strip_prefix(&rules.base) {
+                Ok(rel) => rel,
+                Err(_) => continue,
+            };
+            let name = rel
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default();
+            let rel = rel_path_str(rel);
+            for rule in &rules.rules {
+                if (!rule.dir_only || is_dir) && glob_matches(&rule.glob, &name, &rel) {
+                    ignored = !rule.negated;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<IgnoreRule> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        // A leading `/` only anchors the pattern, which any inner `/` does as well.
+        let glob = match line.strip_prefix('/') {
+            Some(line) => Glob::new_anchored(line),
+            None => Glob::new(line),
+        };
+        Some(IgnoreRule {
+            glob,
+            negated,
+            dir_only,
+        })
+    }
+}
+
+/// A file found by [`Walk`].
+pub(crate) struct WalkEntry {
+    pub(crate) path: PathBuf,
+    pub(crate) rel: PathBuf,
+    /// A symlink to hash as such, rather than the file it points to.
+    pub(crate) symlink: bool,
+    ignore: Option<Arc<IgnoreRules>>,
+}
+
+/// Depth first walk over the files under a root, entries of a directory in file name order.
+pub(crate) struct Walk {
+    pending: Vec<WalkEntry>,
+    options: DirOptions,
+}
+
+impl Walk {
+    pub(crate) fn with_options(root: &Path, options: DirOptions) -> Self {
+        Walk {
+            pending: vec![WalkEntry {
+                path: root.to_path_buf(),
+                rel: PathBuf::new(),
+                symlink: false,
+                ignore: None,
+            }],
+            options,
+        }
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Result<WalkEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(mut entry) = self.pending.pop() {
+            // The root itself is always followed, even when it's a symlink.
+            let meta = if entry.rel.as_os_str().is_empty() {
+                fs::metadata(&entry.path)
+            } else {
+                fs::symlink_metadata(&entry.path)
+            };
+            let meta = match meta {
+                Ok(meta) => meta,
+                Err(e) => return Some(Err(Error::io(Operation::Metadata, &entry.path, e))),
+            };
+            if meta.is_dir() {
+                if self.options.skip(&entry, true) {
+                    continue;
+                }
+                let mut children = match read_dir_names(&entry.path) {
+                    Ok(children) => children,
+                    Err(e) => return Some(Err(Error::io(Operation::ReadDir, &entry.path, e))),
+                };
+                children.sort();
+                let ignore = if self.options.gitignore {
+                    IgnoreRules::load(entry.ignore.clone(), &entry.path, &entry.rel)
+                } else {
+                    None
+                };
+                self.pending
+                    .extend(children.into_iter().rev().map(|name| WalkEntry {
+                        path: entry.path.join(&name),
+                        rel: entry.rel.join(&name),
+                        symlink: false,
+                        ignore: ignore.clone(),
+                    }));
+                continue;
+            }
+            if meta.file_type().is_symlink() && self.options.hash_symlinks {
+                entry.symlink = true;
+            } else if meta.file_type().is_symlink() {
+                match fs::metadata(&entry.path) {
+                    Ok(meta) if meta.is_file() => {}
+                    _ => continue,
+                }
+            } else if !meta.is_file() {
+                continue;
+            }
+            if self.options.skip(&entry, false) {
+                continue;
+            }
+            return Some(Ok(entry));
+        }
+        None
+    }
+}
+
+fn read_dir_names(path: &Path) -> io::Result<Vec<std::ffi::OsString>> {
+    fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect()
+}
@@ -0,0 +1,137 @@
+use std::alloc::{self, Layout};
+use std::fs;
+use std::io::{self, Read};
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::ptr::NonNull;
+
+/// Alignment of buffers, offsets and lengths for direct IO. Covers both 512 byte and 4 KiB
+/// logical block devices.
+pub(crate) const DIRECT_ALIGN: usize = 4096;
+
+/// Size and alignment of a transparent huge page on x86-64 and aarch64 with 4 KiB pages.
+pub(crate) const HUGE_PAGE: usize = 2 << 20;
+
+/// Zeroed heap buffer whose start is aligned to `align` bytes.
+pub(crate) struct AlignedBuf {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+// The buffer owns its memory like a `Box<[u8]>`.
+unsafe impl Send for AlignedBuf {}
+unsafe impl Sync for AlignedBuf {}
+
+impl AlignedBuf {
+    /// `len` is rounded up to a non-zero multiple of `align`, which must be a power of two.
+    pub(crate) fn new(len: usize, align: usize) -> Self {
+        let len = len.max(1).div_ceil(align) * align;
+        let layout = Layout::from_size_align(len, align).expect("invalid buffer layout");
+        let ptr = unsafe { alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout));
+        AlignedBuf { ptr, layout }
+    }
+}
+
+/// Read buffer for [`Options::aligned_buffer`](crate::Options::aligned_buffer): page aligned,
+/// or huge page aligned and advised as such from [`HUGE_PAGE`] bytes on.
+pub(crate) fn page_aligned_buf(len: usize) -> AlignedBuf {
+    if len < HUGE_PAGE {
+        return AlignedBuf::new(len, DIRECT_ALIGN);
+    }
+    let mut buf = AlignedBuf::new(len, HUGE_PAGE);
+    crate::os::advise_hugepage(&mut buf);
+    buf
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.layout.size()) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+/// Open a file bypassing the page cache where the platform and file system allow it: with
+/// `O_DIRECT` on Linux, Android and FreeBSD, `F_NOCACHE` on macOS. Otherwise (e.g. tmpfs rejects
+/// `O_DIRECT`) the file is opened normally.
+pub(crate) fn open_direct(path: &Path) -> io::Result<fs::File> {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        match fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(path)
+        {
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {}
+            res => return res,
+        }
+    }
+    let f = fs::File::open(path)?;
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::unix::io::AsRawFd;
+        // Only a hint, the file just goes through the cache if it fails.
+        unsafe {
+            libc::fcntl(f.as_raw_fd(), libc::F_NOCACHE, 1);
+        }
+    }
+    Ok(f)
+}
+
+/// Reader for a file opened with [`open_direct`], which only ever reads whole aligned blocks
+/// into an aligned buffer and hands them out in whatever sizes the caller asks for.
+pub(crate) struct DirectReader {
+    file: fs::File,
+    buf: AlignedBuf,
+    pos: usize,
+    filled: usize,
+    eof: bool,
+}
+
+impl DirectReader {
+    pub(crate) fn new(file: fs::File, buffer_size: usize) -> Self {
+        DirectReader {
+            file,
+            buf: AlignedBuf::new(buffer_size, DIRECT_ALIGN),
+            pos: 0,
+            filled: 0,
+            eof: false,
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &fs::File {
+        &self.file
+    }
+}
+
+impl Read for DirectReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.filled && !self.eof {
+            let n = self.file.read(&mut self.buf)?;
+            // A partial block means the end of the file: the file offset is no longer aligned,
+            // so reading on would fail with EINVAL.
+            self.eof = n % DIRECT_ALIGN != 0 || n == 0;
+            self.pos = 0;
+            self.filled = n;
+        }
+        let n = out.len().min(self.filled - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,127 @@
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{self, Read};
+
+/// Length of the internal state of Hash_DRBG with SHA-256, 440 bits.
+const SEED_LEN: usize = 55;
+
+/// Most bytes NIST allows in a single generate request, longer ones are split up.
+const MAX_REQUEST: usize = 1 << 16;
+
+/// Hash_DRBG with SHA-256 from NIST SP 800-90A, a reproducible stream of bytes from a seed
+///
+/// The same seed always gives the same bytes for the same sequence of calls. Each call to
+/// [`fill_bytes`](Sha256Rng::fill_bytes) is one generate request of the standard (split in
+/// requests of 64 KiB), so reading 64 bytes at once gives other bytes than reading 32 twice.
+///
+/// The output is only as unpredictable as the seed: seed it from the operating system for
+/// secrets, or with fixed bytes for test data.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::Sha256Rng;
+/// let mut a = Sha256Rng::from_seed(b"test data seed");
+/// let mut b = Sha256Rng::from_seed(b"test data seed");
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// ```
+#[derive(Clone)]
+pub struct Sha256Rng {
+    v: [u8; SEED_LEN],
+    c: [u8; SEED_LEN],
+    reseed_counter: u64,
+}
+
+impl Sha256Rng {
+    /// Instantiate with `seed` as the entropy input and nonce, without personalization string.
+    pub fn from_seed<T: AsRef<[u8]>>(seed: T) -> Self {
+        let v = hash_df(&[seed.as_ref()]);
+        Sha256Rng {
+            c: hash_df(&[&[0x00], &v]),
+            v,
+            reseed_counter: 1,
+        }
+    }
+
+    /// Mix `seed` into the state, as the entropy input of a reseed.
+    pub fn reseed<T: AsRef<[u8]>>(&mut self, seed: T) {
+        self.v = hash_df(&[&[0x01], &self.v, seed.as_ref()]);
+        self.c = hash_df(&[&[0x00], &self.v]);
+        self.reseed_counter = 1;
+    }
+
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for request in dest.chunks_mut(MAX_REQUEST) {
+            self.generate(request);
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn generate(&mut self, dest: &mut [u8]) {
+        let mut data = self.v;
+        for block in dest.chunks_mut(32) {
+            let hash = Sha256::digest(data);
+            block.copy_from_slice(&hash[..block.len()]);
+            add_be(&mut data, &[1]);
+        }
+        let h = Sha256::new_with_prefix([0x03])
+            .chain_update(self.v)
+            .finalize();
+        let c = self.c;
+        add_be(&mut self.v, &h);
+        add_be(&mut self.v, &c);
+        add_be(&mut self.v, &self.reseed_counter.to_be_bytes());
+        self.reseed_counter += 1;
+    }
+}
+
+impl Read for Sha256Rng {
+    /// Fill all of `buf`, this never fails nor ends.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill_bytes(buf);
+        Ok(buf.len())
+    }
+}
+
+impl fmt::Debug for Sha256Rng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sha256Rng").finish_non_exhaustive()
+    }
+}
+
+/// Hash_df of the concatenation of `inputs`, to `SEED_LEN` bytes.
+fn hash_df(inputs: &[&[u8]]) -> [u8; SEED_LEN] {
+    let mut out = [0; SEED_LEN];
+    for (counter, block) in out.chunks_mut(32).enumerate() {
+        let mut sha = Sha256::new_with_prefix([counter as u8 + 1]);
+        sha.update(((SEED_LEN * 8) as u32).to_be_bytes());
+        for input in inputs {
+            sha.update(input);
+        }
+        block.copy_from_slice(&sha.finalize()[..block.len()]);
+    }
+    out
+}
+
+/// `v += x mod 2^440`, both big endian.
+fn add_be(v: &mut [u8; SEED_LEN], x: &[u8]) {
+    let mut carry = 0u16;
+    for i in 0..SEED_LEN {
+        let sum = v[SEED_LEN - 1 - i] as u16
+            + x.len().checked_sub(i + 1).map_or(0, |j| x[j] as u16)
+            + carry;
+        v[SEED_LEN - 1 - i] = sum as u8;
+        carry = sum >> 8;
+    }
+}
@@ -0,0 +1,56 @@
+use crate::dir::Walk;
+use crate::{calc_file, open_file, DirOptions, Error, Operation, Sha256Hash};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Find the files under `roots` with the same content
+///
+/// Files are first grouped by size, and only files sharing their size with another one get
+/// hashed. Each group of duplicates is sorted, and groups are sorted by their first path. A file
+/// reachable from several roots is only counted once.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::find_duplicates;
+/// for group in find_duplicates(["./src"]).unwrap() {
+///     println!("{:?}", group);
+/// }
+/// ```
+pub fn find_duplicates<I, P>(roots: I) -> Result<Vec<Vec<PathBuf>>, Error>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut seen = BTreeSet::new();
+    let mut by_len = HashMap::<u64, Vec<PathBuf>>::new();
+    for root in roots {
+        for entry in Walk::with_options(root.as_ref(), DirOptions::new()) {
+            let path = entry?.path;
+            let canonical =
+                fs::canonicalize(&path).map_err(|e| Error::io(Operation::Metadata, &path, e))?;
+            if !seen.insert(canonical) {
+                continue;
+            }
+            let meta = fs::metadata(&path).map_err(|e| Error::io(Operation::Metadata, &path, e))?;
+            by_len.entry(meta.len()).or_default().push(path);
+        }
+    }
+
+    let mut groups = vec![];
+    for paths in by_len.into_values().filter(|paths| paths.len() > 1) {
+        let mut by_hash = BTreeMap::<Sha256Hash, Vec<PathBuf>>::new();
+        for path in paths {
+            let f = open_file(&path)?;
+            let hash = calc_file(&path, f)?.into();
+            by_hash.entry(hash).or_default().push(path);
+        }
+        groups.extend(by_hash.into_values().filter(|paths| paths.len() > 1));
+    }
+    for group in &mut groups {
+        group.sort();
+    }
+    groups.sort();
+    Ok(groups)
+}
@@ -0,0 +1,221 @@
+use crate::Sha256Hash;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Errors of the path based APIs, e.g. [`try_digest`](crate::try_digest)
+///
+/// I/O errors carry the path, the operation and, for reads, the byte offset they happened at.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{try_digest, Operation};
+/// let err = try_digest("./not_exist.file").unwrap_err();
+/// assert_eq!(err.path().unwrap().to_str(), Some("./not_exist.file"));
+/// assert_eq!(err.operation(), Some(Operation::Open));
+/// assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    Io {
+        source: io::Error,
+        path: Option<PathBuf>,
+        op: Option<Operation>,
+        /// Bytes successfully read before a failed read.
+        offset: Option<u64>,
+    },
+    /// The path is a symlink and [`SymlinkPolicy::Error`](crate::SymlinkPolicy::Error) is set.
+    Symlink(PathBuf),
+    /// The input is longer than [`Options::max_bytes`](crate::Options::max_bytes).
+    TooLarge { path: PathBuf, limit: u64 },
+    /// The content read from `path` doesn't have the expected digest.
+    Mismatch {
+        path: PathBuf,
+        expected: Sha256Hash,
+        actual: Sha256Hash,
+    },
+    /// Setting up a [`DigestWatcher`](crate::DigestWatcher) failed, other than on I/O, which
+    /// gives an [`Error::Io`] with [`Operation::Watch`].
+    Watch {
+        path: Option<PathBuf>,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+/// The operation an [`Error::Io`] happened in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Operation {
+    Open,
+    Metadata,
+    Read,
+    Seek,
+    ReadLink,
+    ReadDir,
+    Write,
+    Watch,
+}
+
+impl Error {
+    pub(crate) fn io(op: Operation, path: &Path, source: io::Error) -> Self {
+        Error::Io {
+            source,
+            path: Some(path.to_path_buf()),
+            op: Some(op),
+            offset: None,
+        }
+    }
+
+    pub(crate) fn read(path: &Path, offset: u64, source: io::Error) -> Self {
+        Error::Io {
+            source,
+            path: Some(path.to_path_buf()),
+            op: Some(Operation::Read),
+            offset: Some(offset),
+        }
+    }
+
+    /// The path the error happened on, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Io { path, .. } => path.as_deref(),
+            Error::Symlink(path) | Error::TooLarge { path, .. } | Error::Mismatch { path, .. } => {
+                Some(path)
+            }
+            Error::Watch { path, .. } => path.as_deref(),
+        }
+    }
+
+    pub fn operation(&self) -> Option<Operation> {
+        match self {
+            Error::Io { op, .. } => *op,
+            _ => None,
+        }
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Error::Io { offset, .. } => *offset,
+            _ => None,
+        }
+    }
+
+    /// The `io::ErrorKind` of the underlying I/O error, `InvalidInput` for other errors.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Io { source, .. } => source.kind(),
+            _ => io::ErrorKind::InvalidInput,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Open => "open",
+            Operation::Metadata => "read metadata of",
+            Operation::Read => "read",
+            Operation::Seek => "seek in",
+            Operation::ReadLink => "read link",
+            Operation::ReadDir => "read directory",
+            Operation::Write => "write",
+            Operation::Watch => "watch",
+        })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io {
+                source,
+                path: Some(path),
+                op,
+                offset,
+            } => {
+                match op {
+                    Some(op) => write!(f, "failed to {} {}", op, path.display())?,
+                    None => write!(f, "{}", path.display())?,
+                }
+                if let Some(offset) = offset {
+                    write!(f, " at byte {}", offset)?;
+                }
+                write!(f, ": {}", source)
+            }
+            Error::Io { source, .. } => source.fmt(f),
+            Error::Symlink(path) => write!(f, "{} is a symlink", path.display()),
+            Error::TooLarge { path, limit } => {
+                write!(f, "{} is larger than {} bytes", path.display(), limit)
+            }
+            Error::Mismatch {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} has digest {}, expected {}",
+                path.display(),
+                actual,
+                expected
+            ),
+            Error::Watch {
+                path: Some(path),
+                source,
+            } => write!(f, "failed to watch {}: {}", path.display(), source),
+            Error::Watch { source, .. } => source.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Watch { source, .. } => Some(&**source),
+            Error::Symlink(_) | Error::TooLarge { .. } | Error::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Error::Io {
+            source,
+            path: None,
+            op: None,
+            offset: None,
+        }
+    }
+}
+
+#[cfg(feature = "notify")]
+impl From<notify::Error> for Error {
+    fn from(e: notify::Error) -> Self {
+        let path = e.paths.first().cloned();
+        match e.kind {
+            notify::ErrorKind::Io(source) => Error::Io {
+                source,
+                path,
+                op: Some(Operation::Watch),
+                offset: None,
+            },
+            _ => Error::Watch {
+                path,
+                source: Box::new(e),
+            },
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Io {
+                source, path: None, ..
+            } => source,
+            e => io::Error::new(e.kind(), e),
+        }
+    }
+}
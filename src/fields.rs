@@ -0,0 +1,37 @@
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+
+/// Hashes several values into one digest without ambiguity
+///
+/// Each field is prefixed with its length as a 64-bit big endian integer, so `("ab", "c")` and
+/// `("a", "bc")` hash differently, unlike with plain concatenation.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::FieldHasher;
+/// let a = FieldHasher::new().field("ab").field("c").finish();
+/// let b = FieldHasher::new().field("a").field("bc").finish();
+/// assert_ne!(a, b);
+/// ```
+#[derive(Clone, Default)]
+pub struct FieldHasher {
+    sha: Sha256,
+}
+
+impl FieldHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field<T: AsRef<[u8]>>(mut self, field: T) -> Self {
+        let field = field.as_ref();
+        self.sha.update((field.len() as u64).to_be_bytes());
+        self.sha.update(field);
+        self
+    }
+
+    pub fn finish(self) -> Sha256Hash {
+        self.sha.finalize().into()
+    }
+}
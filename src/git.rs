@@ -0,0 +1,47 @@
+use crate::{calc_raw_offset, file_len, lower_hex, open_file, Error};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Git object id of a blob with `input` as content, as hex
+///
+/// This is `sha256("blob <len>\0" || input)`, the id git gives the content in repositories
+/// using the SHA-256 object format (`git init --object-format=sha256`), what
+/// `git hash-object` prints there. Repositories using the default SHA-1 format need SHA-1,
+/// which this crate doesn't provide.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::git_blob_digest;
+/// let id = git_blob_digest("");
+/// assert_eq!(id,"473a0f4c3be8a93681a267e3b1e9a7dcda1185436fe141f7749120a303721813");
+/// ```
+pub fn git_blob_digest<T: AsRef<[u8]>>(input: T) -> String {
+    let input = input.as_ref();
+    let mut sha = Sha256::new_with_prefix(blob_header(input.len() as u64));
+    sha.update(input);
+    lower_hex::encode(sha.finalize())
+}
+
+/// Git object id of a blob with the contents of the file at `path`, see [`git_blob_digest`]
+///
+/// The file must not change size while it is read, since the length in the header is taken
+/// beforehand.
+pub fn git_blob_digest_file<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let file = open_file(path)?;
+    let len = file_len(&file, path)?;
+    let sha = Sha256::new_with_prefix(blob_header(len));
+    let (hash, read) =
+        calc_raw_offset(file.take(len), sha).map_err(|(e, n)| Error::read(path, n, e))?;
+    if read != len {
+        let e = io::Error::new(io::ErrorKind::UnexpectedEof, "file shrank while hashing");
+        return Err(Error::read(path, read, e));
+    }
+    Ok(lower_hex::encode(hash))
+}
+
+fn blob_header(len: u64) -> Vec<u8> {
+    format!("blob {}\0", len).into_bytes()
+}
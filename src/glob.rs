@@ -0,0 +1,165 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(char),
+    /// `?`, any character but `/`
+    One,
+    /// `*`, any run of characters without `/`
+    Star,
+    /// `**` not followed by `/`, anything
+    AnyPath,
+    /// `**/`, nothing or any run of complete directories
+    AnyDirs,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Glob {
+    tokens: Vec<Token>,
+    anchored: bool,
+}
+
+impl Glob {
+    pub(crate) fn new(pattern: &str) -> Self {
+        let chars = pattern.chars().collect::<Vec<_>>();
+        let mut tokens = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' if i + 1 < chars.len() => {
+                    tokens.push(Token::Literal(chars[i + 1]));
+                    i += 2;
+                    continue;
+                }
+                '?' => tokens.push(Token::One),
+                '*' if chars.get(i + 1) == Some(&'*') => {
+                    if chars.get(i + 2) == Some(&'/') {
+                        tokens.push(Token::AnyDirs);
+                        i += 3;
+                    } else {
+                        tokens.push(Token::AnyPath);
+                        i += 2;
+                    }
+                    continue;
+                }
+                '*' => tokens.push(Token::Star),
+                '[' => {
+                    if let Some((token, len)) = parse_class(&chars[i..]) {
+                        tokens.push(token);
+                        i += len;
+                        continue;
+                    }
+                    tokens.push(Token::Literal('['));
+                }
+                c => tokens.push(Token::Literal(c)),
+            }
+            i += 1;
+        }
+        Glob {
+            tokens,
+            anchored: pattern.contains('/'),
+        }
+    }
+
+    pub(crate) fn new_anchored(pattern: &str) -> Self {
+        Glob {
+            anchored: true,
+            ..Self::new(pattern)
+        }
+    }
+
+    /// Whether the pattern applies to whole relative paths rather than to file names.
+    pub(crate) fn anchored(&self) -> bool {
+        self.anchored
+    }
+
+    /// Match against a `/` separated relative path.
+    pub(crate) fn matches(&self, path: &str) -> bool {
+        let text = path.chars().collect::<Vec<_>>();
+        match_tokens(&self.tokens, &text)
+    }
+}
+
+fn parse_class(chars: &[char]) -> Option<(Token, usize)> {
+    let mut i = 1;
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+    let mut ranges = vec![];
+    let start = i;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == ']' && i > start {
+            return Some((Token::Class { negated, ranges }, i + 1));
+        }
+        if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|&end| end != ']') {
+            ranges.push((c, chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Backtracking matcher, memoized on (token index, text index) so that patterns with many
+/// wildcards like `a*a*a*a*b` take polynomial rather than exponential time.
+struct Matcher<'a> {
+    tokens: &'a [Token],
+    text: &'a [char],
+    /// Results of `at`, indexed by `token * (text.len() + 1) + pos`.
+    memo: Vec<Option<bool>>,
+}
+
+fn match_tokens(tokens: &[Token], text: &[char]) -> bool {
+    let mut matcher = Matcher {
+        tokens,
+        text,
+        memo: vec![None; (tokens.len() + 1) * (text.len() + 1)],
+    };
+    matcher.at(0, 0)
+}
+
+impl Matcher<'_> {
+    /// Whether `tokens[token..]` matches `text[pos..]`.
+    fn at(&mut self, token: usize, pos: usize) -> bool {
+        let key = token * (self.text.len() + 1) + pos;
+        if let Some(hit) = self.memo[key] {
+            return hit;
+        }
+        let hit = self.compute(token, pos);
+        self.memo[key] = Some(hit);
+        hit
+    }
+
+    fn compute(&mut self, token: usize, pos: usize) -> bool {
+        let text = self.text;
+        let rest = &text[pos..];
+        let next = token + 1;
+        match &self.tokens.get(token) {
+            None => rest.is_empty(),
+            Some(Token::Literal(c)) => rest.first() == Some(c) && self.at(next, pos + 1),
+            Some(Token::One) => rest.first().is_some_and(|&c| c != '/') && self.at(next, pos + 1),
+            Some(Token::Class { negated, ranges }) => match rest.first() {
+                Some(&c) if c != '/' => {
+                    let hit = ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+                    hit != *negated && self.at(next, pos + 1)
+                }
+                _ => false,
+            },
+            Some(Token::Star) => {
+                let end = rest.iter().position(|&c| c == '/').unwrap_or(rest.len());
+                (0..=end).any(|i| self.at(next, pos + i))
+            }
+            Some(Token::AnyPath) => (0..=rest.len()).any(|i| self.at(next, pos + i)),
+            Some(Token::AnyDirs) => {
+                self.at(next, pos)
+                    || (0..rest.len()).any(|i| rest[i] == '/' && self.at(next, pos + i + 1))
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{lower_hex, U256};
+use sha2::digest::Output;
+use sha2::Sha256;
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+/// A raw sha256 digest
+///
+/// Displays as lowercase hex and parses back from hex.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::Sha256Hash;
+/// let hash: Sha256Hash = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".parse().unwrap();
+/// assert_eq!(hash.as_bytes()[0], 0x2c);
+/// assert_eq!(hash.to_string(),"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sha256Hash([u8; 32]);
+
+impl Sha256Hash {
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Sha256Hash(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_hex(&self) -> String {
+        lower_hex::encode(self.0)
+    }
+
+    /// Lowercase hex without allocating, see [`HexDigest`].
+    pub fn to_hex_digest(&self) -> HexDigest {
+        let mut hex = [0u8; 64];
+        lower_hex::encode_to_slice(&self.0, &mut hex);
+        HexDigest(hex)
+    }
+
+    /// Compare in constant time, to avoid leaking through timing how much of a digest matched.
+    pub fn ct_eq(&self, other: &Sha256Hash) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+
+    /// The digest as a big endian integer.
+    pub fn to_u256(&self) -> U256 {
+        U256::from_be_bytes(self.0)
+    }
+
+    /// Whether the digest, as a big endian integer, is at most `target`.
+    pub fn meets_target(&self, target: &U256) -> bool {
+        self.to_u256() <= *target
+    }
+
+    /// The number of zero bits the digest starts with, from 0 to 256.
+    pub fn leading_zero_bits(&self) -> u32 {
+        self.to_u256().leading_zeros()
+    }
+}
+
+impl From<[u8; 32]> for Sha256Hash {
+    fn from(bytes: [u8; 32]) -> Self {
+        Sha256Hash(bytes)
+    }
+}
+
+impl From<Output<Sha256>> for Sha256Hash {
+    fn from(output: Output<Sha256>) -> Self {
+        Sha256Hash(output.into())
+    }
+}
+
+impl From<Sha256Hash> for [u8; 32] {
+    fn from(hash: Sha256Hash) -> Self {
+        hash.0
+    }
+}
+
+impl AsRef<[u8]> for Sha256Hash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Sha256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl fmt::Debug for Sha256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sha256Hash({})", self)
+    }
+}
+
+impl FromStr for Sha256Hash {
+    type Err = hex::FromHexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes)?;
+        Ok(Sha256Hash(bytes))
+    }
+}
+
+/// A digest as 64 lowercase hex digits, stored inline rather than on the heap
+///
+/// Derefs to `&str`, for targets where allocating a `String` per digest is too much.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest_hex, HexDigest};
+/// let hex: HexDigest = digest_hex("hello");
+/// assert_eq!(&*hex,"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HexDigest([u8; 64]);
+
+impl HexDigest {
+    pub fn as_str(&self) -> &str {
+        // Safety: only ever holds hex digits, which are ASCII.
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl Deref for HexDigest {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for HexDigest {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for HexDigest {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for HexDigest {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl fmt::Display for HexDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for HexDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HexDigest({})", self.as_str())
+    }
+}
+
+/// Constant time equality of two byte strings, only their length may leak.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(diff) == 0
+}
@@ -0,0 +1,160 @@
+use crate::{FieldHasher, Sha256Hash};
+
+/// Types with a stable encoding to hash, see [`FieldHasher`]
+///
+/// Each value feeds itself to the hasher as length prefixed fields: integers as big endian
+/// bytes (`usize` and `isize` widened to 64 bits, so digests don't depend on the platform),
+/// `bool` as one byte, `char` as its `u32` scalar value, strings as their UTF-8 bytes, `Option`
+/// as a `0` or `1` byte followed by the value, and sequences as their length followed by
+/// their elements.
+///
+/// With the `macros` feature, `#[derive(Sha256Hashable)]` implements it for structs and enums.
+/// The derived encoding starts with the type name and a version, 0 unless set with
+/// `#[sha256(version = N)]`, then hashes each field name and value in declaration order,
+/// after the variant name for enums. Fields marked `#[sha256(skip)]` are left out. Renaming
+/// or reordering fields changes digests, bump the version when changing a hashed type.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{FieldHasher, Sha256Hashable};
+///
+/// struct User {
+///     name: String,
+///     age: u32,
+/// }
+///
+/// impl Sha256Hashable for User {
+///     fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+///         let hasher = self.name.hash_fields(hasher);
+///         self.age.hash_fields(hasher)
+///     }
+/// }
+///
+/// let user = User { name: "ferris".into(), age: 8 };
+/// assert_eq!(user.sha256(), ("ferris", 8u32).sha256());
+/// ```
+pub trait Sha256Hashable {
+    /// Feeds the encoding of `self` to `hasher`.
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher;
+
+    /// The digest of the encoding of `self`.
+    fn sha256(&self) -> Sha256Hash {
+        self.hash_fields(FieldHasher::new()).finish()
+    }
+}
+
+macro_rules! impl_int {
+    ($($int:ty),*) => {$(
+        impl Sha256Hashable for $int {
+            fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+                hasher.field(self.to_be_bytes())
+            }
+        }
+    )*};
+}
+
+impl_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Sha256Hashable for usize {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        (*self as u64).hash_fields(hasher)
+    }
+}
+
+impl Sha256Hashable for isize {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        (*self as i64).hash_fields(hasher)
+    }
+}
+
+impl Sha256Hashable for bool {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        (*self as u8).hash_fields(hasher)
+    }
+}
+
+impl Sha256Hashable for char {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        (*self as u32).hash_fields(hasher)
+    }
+}
+
+impl Sha256Hashable for str {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        hasher.field(self)
+    }
+}
+
+impl Sha256Hashable for String {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        hasher.field(self)
+    }
+}
+
+impl Sha256Hashable for Sha256Hash {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        hasher.field(self.as_bytes())
+    }
+}
+
+impl<T: Sha256Hashable> Sha256Hashable for Option<T> {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        match self {
+            None => hasher.field([0]),
+            Some(value) => value.hash_fields(hasher.field([1])),
+        }
+    }
+}
+
+impl<T: Sha256Hashable> Sha256Hashable for [T] {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        let hasher = self.len().hash_fields(hasher);
+        self.iter()
+            .fold(hasher, |hasher, item| item.hash_fields(hasher))
+    }
+}
+
+impl<T: Sha256Hashable, const N: usize> Sha256Hashable for [T; N] {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        self[..].hash_fields(hasher)
+    }
+}
+
+impl<T: Sha256Hashable> Sha256Hashable for Vec<T> {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        self[..].hash_fields(hasher)
+    }
+}
+
+impl<T: Sha256Hashable + ?Sized> Sha256Hashable for &T {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        (**self).hash_fields(hasher)
+    }
+}
+
+impl<T: Sha256Hashable + ?Sized> Sha256Hashable for Box<T> {
+    fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+        (**self).hash_fields(hasher)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: Sha256Hashable),+> Sha256Hashable for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn hash_fields(&self, hasher: FieldHasher) -> FieldHasher {
+                let ($($name,)+) = self;
+                $(let hasher = $name.hash_fields(hasher);)+
+                hasher
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A B);
+impl_tuple!(A B C);
+impl_tuple!(A B C D);
+impl_tuple!(A B C D E);
+impl_tuple!(A B C D E F);
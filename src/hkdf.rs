@@ -0,0 +1,63 @@
+//! HKDF-SHA256 key derivation ([RFC 5869](https://www.rfc-editor.org/rfc/rfc5869))
+//!
+//! ```rust
+//! use sha256::hkdf;
+//! let okm = hkdf::derive(b"salt", b"input key material", b"context", 42);
+//! assert_eq!(okm.len(), 42);
+//! ```
+
+use crate::{hmac_raw, HmacSha256};
+
+/// Most bytes [`expand`] can produce, 255 blocks of 32 bytes.
+pub const MAX_OUTPUT_LEN: usize = 255 * 32;
+
+/// Extract a pseudorandom key from the input keying material `ikm`
+///
+/// An empty `salt` is the same as no salt, 32 zero bytes.
+pub fn extract<S: AsRef<[u8]>, I: AsRef<[u8]>>(salt: S, ikm: I) -> [u8; 32] {
+    let salt = salt.as_ref();
+    if salt.is_empty() {
+        hmac_raw([0u8; 32], ikm)
+    } else {
+        hmac_raw(salt, ikm)
+    }
+}
+
+/// Expand the pseudorandom key `prk` into `len` bytes of output keying material bound to `info`
+///
+/// # Panics
+///
+/// If `len` is larger than [`MAX_OUTPUT_LEN`].
+pub fn expand<P: AsRef<[u8]>, I: AsRef<[u8]>>(prk: P, info: I, len: usize) -> Vec<u8> {
+    assert!(len <= MAX_OUTPUT_LEN, "HKDF output is at most 8160 bytes");
+    let mac = HmacSha256::new(prk);
+    let mut okm = Vec::with_capacity(len);
+    let mut block: Option<[u8; 32]> = None;
+    for counter in 1..=len.div_ceil(32) as u8 {
+        let mut mac = mac.clone();
+        if let Some(block) = &block {
+            mac.update(block);
+        }
+        mac.update(info.as_ref());
+        mac.update([counter]);
+        let next = mac.finalize();
+        let n = (len - okm.len()).min(32);
+        okm.extend_from_slice(&next[..n]);
+        block = Some(next);
+    }
+    okm
+}
+
+/// [`extract`] then [`expand`]
+///
+/// # Panics
+///
+/// If `len` is larger than [`MAX_OUTPUT_LEN`].
+pub fn derive<S, K, I>(salt: S, ikm: K, info: I, len: usize) -> Vec<u8>
+where
+    S: AsRef<[u8]>,
+    K: AsRef<[u8]>,
+    I: AsRef<[u8]>,
+{
+    expand(extract(salt, ikm), info, len)
+}
@@ -0,0 +1,143 @@
+use crate::{calc_path, lower_hex, open_file, CalculatorSelector, Error};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+
+/// Block size of sha256, the size HMAC pads keys to.
+const BLOCK_SIZE: usize = 64;
+
+/// HMAC-SHA256 of `message` under `key`, as lowercase hex
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::hmac;
+/// let val = hmac("key", "The quick brown fox jumps over the lazy dog");
+/// assert_eq!(val,"f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8");
+/// ```
+pub fn hmac<K: AsRef<[u8]>, M: AsRef<[u8]>>(key: K, message: M) -> String {
+    lower_hex::encode(hmac_raw(key, message))
+}
+
+/// HMAC-SHA256 of `message` under `key`, as raw bytes
+///
+/// Compare tags with [`Sha256Hash::ct_eq`](crate::Sha256Hash::ct_eq) rather than `==`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::hmac_raw;
+/// let tag = hmac_raw("key", "The quick brown fox jumps over the lazy dog");
+/// assert_eq!(tag[0], 0xf7);
+/// ```
+pub fn hmac_raw<K: AsRef<[u8]>, M: AsRef<[u8]>>(key: K, message: M) -> [u8; 32] {
+    let mut mac = HmacSha256::new(key);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// HMAC-SHA256 of a file under `key`, streaming it, as lowercase hex
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{hmac, try_hmac_file};
+/// let bytes = std::fs::read("./foo.file").unwrap();
+/// assert_eq!(try_hmac_file("key", "./foo.file").unwrap(), hmac("key", bytes));
+/// ```
+pub fn try_hmac_file<K: AsRef<[u8]>, P: AsRef<Path>>(key: K, path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    Ok(lower_hex::encode(calc_path(
+        path,
+        0,
+        f,
+        HmacSha256::new(key),
+    )?))
+}
+
+/// Like [`try_hmac_file`], reading the file asynchronously
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{hmac, try_async_hmac_file};
+/// let bytes = std::fs::read("./foo.file").unwrap();
+/// tokio_test::block_on(async{
+/// let val = try_async_hmac_file("key", "./foo.file").await.unwrap();
+/// assert_eq!(val, hmac("key", bytes));
+/// });
+/// ```
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+pub async fn try_async_hmac_file<K: AsRef<[u8]>, P: AsRef<Path>>(
+    key: K,
+    path: P,
+) -> Result<String, Error> {
+    let mac = HmacSha256::new(key);
+    Ok(lower_hex::encode(
+        crate::async_calc_path(path.as_ref(), mac).await?,
+    ))
+}
+
+/// Incremental HMAC-SHA256, for messages that come in pieces
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{hmac_raw, HmacSha256};
+/// let mut mac = HmacSha256::new("key");
+/// mac.update("The quick brown fox ");
+/// mac.update("jumps over the lazy dog");
+/// assert_eq!(mac.finalize(), hmac_raw("key", "The quick brown fox jumps over the lazy dog"));
+/// ```
+#[derive(Clone)]
+pub struct HmacSha256 {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl HmacSha256 {
+    pub fn new<K: AsRef<[u8]>>(key: K) -> Self {
+        let key = key.as_ref();
+        let mut block = [0u8; BLOCK_SIZE];
+        if key.len() > BLOCK_SIZE {
+            block[..32].copy_from_slice(&Sha256::digest(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let pad = |byte: u8| Sha256::new_with_prefix(block.map(|b| b ^ byte));
+        HmacSha256 {
+            inner: pad(0x36),
+            outer: pad(0x5c),
+        }
+    }
+
+    pub fn update<M: AsRef<[u8]>>(&mut self, message: M) {
+        self.inner.update(message);
+    }
+
+    /// The tag of everything passed to [`update`](HmacSha256::update).
+    pub fn finalize(self) -> [u8; 32] {
+        let mut outer = self.outer;
+        outer.update(self.inner.finalize());
+        outer.finalize().into()
+    }
+}
+
+impl CalculatorSelector for HmacSha256 {
+    type FinishType = [u8; 32];
+
+    fn update_inner(&mut self, data: &[u8]) {
+        self.update(data)
+    }
+
+    fn finish_inner(self) -> Self::FinishType {
+        self.finalize()
+    }
+}
+
+impl fmt::Debug for HmacSha256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSha256").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,641 @@
+use crate::lower_hex;
+use serde::ser::{self, Impossible, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Integers from -2^53 to 2^53 are exactly representable as JSON numbers (IEEE 754 doubles).
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// sha256 digest the canonical JSON of `value`, as hex
+///
+/// The value is serialized with the JSON Canonicalization Scheme
+/// ([RFC 8785](https://www.rfc-editor.org/rfc/rfc8785)): no whitespace, object keys sorted by
+/// their UTF-16 code units, numbers formatted like ECMAScript and minimal string escaping. Any
+/// JCS implementation, in any language, gives the same bytes for the same data.
+///
+/// Types map to JSON as they do with `serde_json`. Map keys must be strings, and numbers must
+/// be finite and, for integers, within ±2<sup>53</sup> so that they survive as doubles.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, digest_json};
+/// use std::collections::HashMap;
+/// let value = HashMap::from([("b", 1.0), ("a", 0.5)]);
+/// assert_eq!(digest_json(&value).unwrap(), digest(r#"{"a":0.5,"b":1}"#));
+/// ```
+pub fn digest_json<T: Serialize + ?Sized>(value: &T) -> Result<String, JsonError> {
+    let json = to_canonical_json(value)?;
+    Ok(lower_hex::encode(Sha256::digest(json)))
+}
+
+/// The RFC 8785 canonical JSON of `value`, what [`digest_json`] hashes
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, JsonError> {
+    let mut out = String::new();
+    value.serialize(Canonical { out: &mut out })?;
+    Ok(out)
+}
+
+/// Why a value has no canonical JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    /// NaN or an infinity.
+    NonFiniteNumber,
+    /// An integer a double can't represent exactly.
+    IntegerOutOfRange,
+    /// A map key that doesn't serialize as a string.
+    KeyNotString,
+    /// An error of the `Serialize` implementation.
+    Custom(String),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::NonFiniteNumber => f.write_str("NaN and infinities are not JSON numbers"),
+            JsonError::IntegerOutOfRange => f.write_str("integer outside of +/-2^53"),
+            JsonError::KeyNotString => f.write_str("JSON object keys must be strings"),
+            JsonError::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl ser::Error for JsonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        JsonError::Custom(msg.to_string())
+    }
+}
+
+struct Canonical<'a> {
+    out: &'a mut String,
+}
+
+impl Canonical<'_> {
+    fn integer(self, negative: bool, magnitude: u128) -> Result<(), JsonError> {
+        if magnitude > MAX_SAFE_INTEGER as u128 {
+            return Err(JsonError::IntegerOutOfRange);
+        }
+        if negative && magnitude != 0 {
+            self.out.push('-');
+        }
+        self.out.push_str(&magnitude.to_string());
+        Ok(())
+    }
+
+    /// `{"variant":`, closed by the compound serializer of the variant's content.
+    fn open_variant(&mut self, variant: &str) {
+        self.out.push('{');
+        write_str(self.out, variant);
+        self.out.push(':');
+    }
+}
+
+impl<'a> ser::Serializer for Canonical<'a> {
+    type Ok = ();
+    type Error = JsonError;
+    type SerializeSeq = Seq<'a>;
+    type SerializeTuple = Seq<'a>;
+    type SerializeTupleStruct = Seq<'a>;
+    type SerializeTupleVariant = Seq<'a>;
+    type SerializeMap = Map<'a>;
+    type SerializeStruct = Map<'a>;
+    type SerializeStructVariant = Map<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), JsonError> {
+        self.out.push_str(if v { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), JsonError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), JsonError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), JsonError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), JsonError> {
+        self.integer(v < 0, v.unsigned_abs() as u128)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), JsonError> {
+        self.integer(v < 0, v.unsigned_abs())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), JsonError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), JsonError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), JsonError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), JsonError> {
+        self.integer(false, v as u128)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), JsonError> {
+        self.integer(false, v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), JsonError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), JsonError> {
+        if !v.is_finite() {
+            return Err(JsonError::NonFiniteNumber);
+        }
+        write_number(self.out, v);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), JsonError> {
+        write_str(self.out, v.encode_utf8(&mut [0; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), JsonError> {
+        write_str(self.out, v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), JsonError> {
+        ser::Serializer::collect_seq(self, v)
+    }
+
+    fn serialize_none(self) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), JsonError> {
+        self.out.push_str("null");
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), JsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        mut self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.open_variant(variant);
+        value.serialize(Canonical {
+            out: &mut *self.out,
+        })?;
+        self.out.push('}');
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Seq<'a>, JsonError> {
+        self.out.push('[');
+        Ok(Seq {
+            out: self.out,
+            first: true,
+            variant: false,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Seq<'a>, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Seq<'a>, JsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Seq<'a>, JsonError> {
+        self.open_variant(variant);
+        let mut seq = self.serialize_seq(Some(len))?;
+        seq.variant = true;
+        Ok(seq)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Map<'a>, JsonError> {
+        Ok(Map {
+            out: self.out,
+            entries: Vec::new(),
+            key: None,
+            variant: false,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Map<'a>, JsonError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Map<'a>, JsonError> {
+        self.open_variant(variant);
+        let mut map = self.serialize_map(Some(len))?;
+        map.variant = true;
+        Ok(map)
+    }
+}
+
+/// Arrays, closed with `}` too when the content of an enum variant.
+struct Seq<'a> {
+    out: &'a mut String,
+    first: bool,
+    variant: bool,
+}
+
+impl Seq<'_> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        if !self.first {
+            self.out.push(',');
+        }
+        self.first = false;
+        value.serialize(Canonical {
+            out: &mut *self.out,
+        })
+    }
+
+    fn close(self) -> Result<(), JsonError> {
+        self.out.push(']');
+        if self.variant {
+            self.out.push('}');
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for Seq<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTuple for Seq<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleStruct for Seq<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleVariant for Seq<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+/// Objects, whose members are collected and written sorted on `end`.
+struct Map<'a> {
+    out: &'a mut String,
+    /// Keys and serialized values.
+    entries: Vec<(String, String)>,
+    key: Option<String>,
+    variant: bool,
+}
+
+impl Map<'_> {
+    fn entry<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), JsonError> {
+        let mut json = String::new();
+        value.serialize(Canonical { out: &mut json })?;
+        self.entries.push((key, json));
+        Ok(())
+    }
+
+    fn close(mut self) -> Result<(), JsonError> {
+        self.entries
+            .sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+        self.out.push('{');
+        for (i, (key, value)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            write_str(self.out, key);
+            self.out.push(':');
+            self.out.push_str(value);
+        }
+        self.out.push('}');
+        if self.variant {
+            self.out.push('}');
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for Map<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), JsonError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStruct for Map<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.entry(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStructVariant for Map<'_> {
+    type Ok = ();
+    type Error = JsonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        self.entry(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.close()
+    }
+}
+
+/// Map keys, which have to be strings.
+struct KeySerializer;
+
+macro_rules! not_a_string_key {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<String, JsonError> {
+                Err(JsonError::KeyNotString)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = JsonError;
+    type SerializeSeq = Impossible<String, JsonError>;
+    type SerializeTuple = Impossible<String, JsonError>;
+    type SerializeTupleStruct = Impossible<String, JsonError>;
+    type SerializeTupleVariant = Impossible<String, JsonError>;
+    type SerializeMap = Impossible<String, JsonError>;
+    type SerializeStruct = Impossible<String, JsonError>;
+    type SerializeStructVariant = Impossible<String, JsonError>;
+
+    fn serialize_str(self, v: &str) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, JsonError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, JsonError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, JsonError> {
+        value.serialize(self)
+    }
+
+    not_a_string_key!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_bytes(&[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, JsonError> {
+        Err(JsonError::KeyNotString)
+    }
+}
+
+/// A JSON string with the escaping of ECMAScript's `JSON.stringify`.
+fn write_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// A finite number formatted like ECMAScript's `Number.prototype.toString`.
+fn write_number(out: &mut String, v: f64) {
+    if v == 0.0 {
+        out.push('0');
+        return;
+    }
+    if v < 0.0 {
+        out.push('-');
+    }
+    // Rust's exponent format has the shortest digits that round trip, like ECMAScript.
+    let sci = format!("{:e}", v.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // The value is 0.digits * 10^n.
+    let n = exponent.parse::<i32>().unwrap() + 1;
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.extend(std::iter::repeat_n('0', (n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', -n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n > 0 { '+' } else { '-' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+}
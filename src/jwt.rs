@@ -0,0 +1,291 @@
+//! JSON Web Tokens signed with HS256 (HMAC-SHA256)
+//!
+//! Only the signature and the `alg` header are checked, the claims are handed back as JSON for
+//! the caller to parse and validate (`exp`, `aud`, ...).
+//!
+//! ```rust
+//! use sha256::jwt;
+//! let token = jwt::sign(r#"{"sub":"1234567890"}"#, b"secret");
+//! assert_eq!(jwt::verify(&token, b"secret").unwrap(), r#"{"sub":"1234567890"}"#);
+//! assert!(jwt::verify(&token, b"other secret").is_err());
+//! ```
+
+use crate::base64::{decode_url, encode_url};
+use crate::hash::ct_eq;
+use crate::hmac_raw;
+use std::fmt;
+
+/// `{"alg":"HS256","typ":"JWT"}`, encoded.
+const HEADER: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9";
+
+/// Why [`verify`] rejected a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtError {
+    /// Not three canonical base64url parts, not UTF-8, or a header that isn't a JSON object
+    /// with at most one `alg`.
+    Malformed,
+    /// The header doesn't say `"alg":"HS256"`.
+    Algorithm,
+    /// The signature doesn't match.
+    Signature,
+}
+
+impl fmt::Display for JwtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            JwtError::Malformed => "malformed token",
+            JwtError::Algorithm => "token isn't signed with HS256",
+            JwtError::Signature => "invalid token signature",
+        })
+    }
+}
+
+impl std::error::Error for JwtError {}
+
+/// A token for the JSON `claims`, signed with `key`
+pub fn sign<K: AsRef<[u8]>>(claims: &str, key: K) -> String {
+    let signing_input = format!("{}.{}", HEADER, encode_url(claims.as_bytes()));
+    let signature = encode_url(&hmac_raw(key, &signing_input));
+    format!("{}.{}", signing_input, signature)
+}
+
+/// The JSON claims of `token`, if it is signed with `key` using HS256
+pub fn verify<K: AsRef<[u8]>>(token: &str, key: K) -> Result<String, JwtError> {
+    let mut parts = token.split('.');
+    let (header, claims, signature) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(header), Some(claims), Some(signature)) if parts.next().is_none() => {
+            (header, claims, signature)
+        }
+        _ => return Err(JwtError::Malformed),
+    };
+    let decode = |part| decode_url(part).ok_or(JwtError::Malformed);
+    let header = String::from_utf8(decode(header)?).map_err(|_| JwtError::Malformed)?;
+    if header_alg(&header)?.as_deref() != Some("HS256") {
+        return Err(JwtError::Algorithm);
+    }
+    let signing_input = &token[..token.len() - signature.len() - 1];
+    if !ct_eq(&hmac_raw(key, signing_input), &decode(signature)?) {
+        return Err(JwtError::Signature);
+    }
+    String::from_utf8(decode(claims)?).map_err(|_| JwtError::Malformed)
+}
+
+/// The `alg` member of a JSON header, if it has one and it is a string.
+fn header_alg(header: &str) -> Result<Option<String>, JwtError> {
+    let mut json = Json {
+        s: header.as_bytes(),
+        pos: 0,
+    };
+    let mut alg = None;
+    json.expect(b'{')?;
+    if !json.eat(b'}') {
+        loop {
+            let key = json.string()?;
+            json.expect(b':')?;
+            let value = json.value(0)?;
+            // A second `alg` could be read differently by another parser, so it is refused.
+            if key == "alg" && alg.replace(value).is_some() {
+                return Err(JwtError::Malformed);
+            }
+            if json.eat(b'}') {
+                break;
+            }
+            json.expect(b',')?;
+        }
+    }
+    json.skip_ws();
+    if json.pos != json.s.len() {
+        return Err(JwtError::Malformed);
+    }
+    Ok(alg.flatten())
+}
+
+/// Nesting allowed in header values, deeper headers are refused rather than recursed into.
+const MAX_DEPTH: usize = 32;
+
+/// Just enough of a JSON parser to read a header: values other than strings are checked and
+/// skipped.
+struct Json<'a> {
+    s: &'a [u8],
+    pos: usize,
+}
+
+impl Json<'_> {
+    fn skip_ws(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.s.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.s.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), JwtError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(JwtError::Malformed)
+        }
+    }
+
+    fn next(&mut self) -> Result<u8, JwtError> {
+        let c = *self.s.get(self.pos).ok_or(JwtError::Malformed)?;
+        self.pos += 1;
+        Ok(c)
+    }
+
+    /// A value, returned if it is a string.
+    fn value(&mut self, depth: usize) -> Result<Option<String>, JwtError> {
+        if depth == MAX_DEPTH {
+            return Err(JwtError::Malformed);
+        }
+        match self.peek().ok_or(JwtError::Malformed)? {
+            b'"' => return self.string().map(Some),
+            b'{' => {
+                self.pos += 1;
+                if !self.eat(b'}') {
+                    loop {
+                        self.string()?;
+                        self.expect(b':')?;
+                        self.value(depth + 1)?;
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+            }
+            b'[' => {
+                self.pos += 1;
+                if !self.eat(b']') {
+                    loop {
+                        self.value(depth + 1)?;
+                        if self.eat(b']') {
+                            break;
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+            }
+            b't' => self.literal(b"true")?,
+            b'f' => self.literal(b"false")?,
+            b'n' => self.literal(b"null")?,
+            _ => self.number()?,
+        }
+        Ok(None)
+    }
+
+    fn literal(&mut self, word: &[u8]) -> Result<(), JwtError> {
+        if !self.s[self.pos..].starts_with(word) {
+            return Err(JwtError::Malformed);
+        }
+        self.pos += word.len();
+        Ok(())
+    }
+
+    fn number(&mut self) -> Result<(), JwtError> {
+        if self.s.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        match self.next()? {
+            b'0' => {}
+            b'1'..=b'9' => self.digits(),
+            _ => return Err(JwtError::Malformed),
+        }
+        if self.s.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            self.digit()?;
+        }
+        if let Some(b'e' | b'E') = self.s.get(self.pos) {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.s.get(self.pos) {
+                self.pos += 1;
+            }
+            self.digit()?;
+        }
+        Ok(())
+    }
+
+    /// One or more digits.
+    fn digit(&mut self) -> Result<(), JwtError> {
+        if !self.next()?.is_ascii_digit() {
+            return Err(JwtError::Malformed);
+        }
+        self.digits();
+        Ok(())
+    }
+
+    fn digits(&mut self) {
+        while self.s.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JwtError> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+        loop {
+            match self.next()? {
+                b'"' => break,
+                b'\\' => {
+                    let c = match self.next()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.escaped_char()?,
+                        _ => return Err(JwtError::Malformed),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                c if c < 0x20 => return Err(JwtError::Malformed),
+                c => out.push(c),
+            }
+        }
+        // Only whole characters of the (UTF-8) header were copied.
+        String::from_utf8(out).map_err(|_| JwtError::Malformed)
+    }
+
+    /// The character of a `\u` escape, reading the low half of a surrogate pair if need be.
+    fn escaped_char(&mut self) -> Result<char, JwtError> {
+        let high = self.hex4()?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            self.literal(b"\\u")?;
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(JwtError::Malformed);
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or(JwtError::Malformed)
+    }
+
+    fn hex4(&mut self) -> Result<u32, JwtError> {
+        let hex = self
+            .s
+            .get(self.pos..self.pos + 4)
+            .ok_or(JwtError::Malformed)?;
+        self.pos += 4;
+        let hex = std::str::from_utf8(hex).map_err(|_| JwtError::Malformed)?;
+        if !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(JwtError::Malformed);
+        }
+        u32::from_str_radix(hex, 16).map_err(|_| JwtError::Malformed)
+    }
+}
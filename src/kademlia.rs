@@ -0,0 +1,49 @@
+use crate::Sha256Hash;
+use std::cmp::Ordering;
+
+/// Kademlia distance between two ids, their bitwise XOR as a 256-bit big endian number
+///
+/// Distances compare as byte arrays, so `[u8; 32]` ordering is distance ordering.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{xor_distance, Sha256Hash};
+/// let a = Sha256Hash::new([0xff; 32]);
+/// assert_eq!(xor_distance(&a, &a), [0; 32]);
+/// assert_eq!(xor_distance(&a, &Sha256Hash::default()), [0xff; 32]);
+/// ```
+pub fn xor_distance(a: &Sha256Hash, b: &Sha256Hash) -> [u8; 32] {
+    let mut distance = [0; 32];
+    for ((d, x), y) in distance.iter_mut().zip(a.as_bytes()).zip(b.as_bytes()) {
+        *d = x ^ y;
+    }
+    distance
+}
+
+/// How the distances of `a` and `b` to `target` compare, to sort ids by distance
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{cmp_distance, Sha256Hash};
+/// let target = Sha256Hash::default();
+/// let mut ids = vec![Sha256Hash::new([3; 32]), Sha256Hash::new([1; 32])];
+/// ids.sort_by(|a, b| cmp_distance(&target, a, b));
+/// assert_eq!(ids[0], Sha256Hash::new([1; 32]));
+/// ```
+pub fn cmp_distance(target: &Sha256Hash, a: &Sha256Hash, b: &Sha256Hash) -> Ordering {
+    xor_distance(target, a).cmp(&xor_distance(target, b))
+}
+
+/// Index of the k-bucket of `own` that `other` belongs in, `None` if they're the same id
+///
+/// Bucket `i` holds the ids at a distance in `[2^i, 2^(i+1))`, that is ids sharing their first
+/// `255 - i` bits with `own` and differing on the next one.
+pub fn bucket_index(own: &Sha256Hash, other: &Sha256Hash) -> Option<usize> {
+    let distance = Sha256Hash::new(xor_distance(own, other));
+    match distance.leading_zero_bits() {
+        256 => None,
+        zeros => Some(255 - zeros as usize),
+    }
+}
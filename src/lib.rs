@@ -37,24 +37,176 @@
 //!
 //! ```
 
+// Lets the derive macros' `::sha256` paths resolve in the crate's own tests.
+#[cfg(all(test, feature = "macros"))]
+extern crate self as sha256;
+
+#[cfg(any(feature = "tar", feature = "zip"))]
+mod archive;
 #[cfg(feature = "async")]
 pub mod async_digest;
+mod atomic;
+mod base58;
+mod base64;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub mod bench;
+mod bloom;
+#[cfg(feature = "http_body")]
+pub mod body_digest;
+mod cache;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "cdc")]
+mod cdc;
+pub mod chain;
+#[cfg(feature = "cid")]
+mod cid;
+mod commit;
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+mod compressed;
+mod dir;
+mod direct;
+mod drbg;
+mod duplicates;
+mod error;
+mod fields;
+mod git;
+mod glob;
+mod hash;
+mod hashable;
+#[cfg(feature = "hmac")]
+pub mod hkdf;
+#[cfg(feature = "hmac")]
+mod hmac;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(feature = "hmac")]
+pub mod jwt;
+mod kademlia;
+mod lower_hex;
+pub mod manifest;
+pub mod merkle;
+mod midstate;
+#[cfg(feature = "mmap")]
+mod mmap;
 #[cfg(feature = "native_openssl")]
 mod openssl_sha256;
-
-#[cfg(feature = "native_openssl")]
+mod options;
+mod os;
+#[cfg(feature = "hmac")]
+pub mod otp;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+#[cfg(feature = "hmac")]
+mod pbkdf2;
+mod pipeline;
+pub mod pkcs1;
+mod pool;
+pub mod pow;
+mod report;
+#[cfg(feature = "reqwest")]
+pub mod reqwest_digest;
+mod ring;
+mod s3;
+pub mod sampling;
+#[cfg(not(target_arch = "wasm32"))]
+mod service;
+#[cfg(feature = "hmac")]
+mod sigv4;
+mod sparse;
+mod store;
+mod throttle;
+pub mod torrent;
+mod u256;
+#[cfg(feature = "notify")]
+mod watch;
+#[cfg(feature = "hmac")]
+mod webhook;
+
+#[cfg(all(
+    feature = "async",
+    feature = "native_openssl",
+    not(target_arch = "wasm32")
+))]
 use crate::openssl_sha256::OpenSslSha256;
 
+#[cfg(any(feature = "tar", feature = "zip"))]
+pub use archive::*;
 #[cfg(feature = "async")]
 pub use async_digest::*;
+pub use atomic::{copy_verified, write_with_digest};
+pub use base58::{base58check_decode, base58check_encode, Base58Error};
+pub use bloom::BloomFilter;
+#[cfg(feature = "http_body")]
+pub use body_digest::*;
+pub use cache::DigestCache;
+#[cfg(feature = "cbor")]
+pub use cbor::{digest_cbor, to_canonical_cbor, CborError};
+#[cfg(feature = "cdc")]
+pub use cdc::{try_digest_cdc, CdcDigest, CdcOptions, Chunk};
+#[cfg(feature = "cid")]
+pub use cid::{cid_v1_raw, cid_v1_raw_from_hash, try_cid_v1_raw};
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use commit::commit;
+pub use commit::{commit_with_opening, verify_commit, Commitment, Opening};
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use compressed::*;
+pub use dir::{
+    digest_dir_report, try_digest_dir, try_digest_dir_with, walk_digest, walk_digest_with,
+    DirOptions, DirReport, WalkDigest,
+};
+pub use drbg::Sha256Rng;
+pub use duplicates::find_duplicates;
+pub use error::{Error, Operation};
+pub use fields::FieldHasher;
+pub use git::{git_blob_digest, git_blob_digest_file};
+pub use hash::{HexDigest, Sha256Hash};
+pub use hashable::Sha256Hashable;
+#[cfg(feature = "hmac")]
+pub use hmac::*;
+#[cfg(feature = "serde_json")]
+pub use json::{digest_json, to_canonical_json, JsonError};
+pub use kademlia::{bucket_index, cmp_distance, xor_distance};
+pub use midstate::{tagged_digest, tagged_midstate, Sha256Midstate};
+#[cfg(feature = "mmap")]
+pub use mmap::try_digest_mmap;
+pub use options::{Options, SymlinkPolicy};
+#[cfg(feature = "parallel")]
+pub use parallel::*;
+#[cfg(feature = "hmac")]
+pub use pbkdf2::{pbkdf2, pbkdf2_verify, PBKDF2_MIN_KEY_LEN};
+pub use pool::{digest_into, with_hasher};
+pub use report::DigestReport;
+#[cfg(feature = "reqwest")]
+pub use reqwest_digest::*;
+pub use ring::{rendezvous_select, rendezvous_top, HashRing};
+pub use s3::s3_composite_checksum;
+#[cfg(not(target_arch = "wasm32"))]
+pub use service::{HashHandle, HashService, PendingDigest};
+#[cfg(feature = "macros")]
+pub use sha256_macros::{sha256, sha256_hex, Sha256Hashable};
+#[cfg(feature = "hmac")]
+pub use sigv4::{sigv4_signature, sigv4_signing_key, sigv4_string_to_sign};
+pub use sparse::try_digest_sparse;
+pub use store::BlobStore;
+pub use u256::U256;
+#[cfg(feature = "notify")]
+pub use watch::DigestWatcher;
+#[cfg(feature = "hmac")]
+pub use webhook::*;
 
 use sha2::digest::Output;
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::fs;
+#[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+use std::future::Future;
 use std::io;
-use std::io::{BufReader, Read};
-use std::path::Path;
+use std::io::{IoSliceMut, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use throttle::{Throttle, Throttled};
 
 #[cfg(test)]
 mod tests;
@@ -74,6 +226,88 @@ pub fn digest<D: Sha256Digest>(input: D) -> String {
     input.digest()
 }
 
+/// sha256 digest bytes as hex, without allocating
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_hex;
+/// let val = digest_hex("hello");
+/// assert_eq!(val,"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+/// ```
+pub fn digest_hex<T: AsRef<[u8]>>(input: T) -> HexDigest {
+    Sha256Hash::from(Sha256::digest(input)).to_hex_digest()
+}
+
+/// Double sha256, `sha256(sha256(input))`, as hex
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest2;
+/// let val = digest2("hello");
+/// assert_eq!(val,"9595c9df90075148eb06860365df33584b75bff782a510c6cd4883a419833d50")
+/// ```
+pub fn digest2<T: AsRef<[u8]>>(input: T) -> String {
+    lower_hex::encode(digest2_raw(input))
+}
+
+/// Double sha256, `sha256(sha256(input))`, as raw bytes, as used for Bitcoin block and
+/// transaction ids
+pub fn digest2_raw<T: AsRef<[u8]>>(input: T) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(input)).into()
+}
+
+/// sha256 digest `input` with a salt, as hex
+///
+/// The salt is length-prefixed so that no two (salt, input) pairs hash the same input bytes:
+///
+/// ```text
+/// sha256(len(salt) as u64 big endian || salt || input)
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_salted;
+/// assert_ne!(digest_salted("ab", "c"), digest_salted("a", "bc"));
+/// ```
+pub fn digest_salted<S: AsRef<[u8]>, T: AsRef<[u8]>>(salt: S, input: T) -> String {
+    let salt = salt.as_ref();
+    let mut sha = Sha256::new_with_prefix((salt.len() as u64).to_be_bytes());
+    sha.update(salt);
+    sha.update(input);
+    lower_hex::encode(sha.finalize())
+}
+
+/// Domain tag starting every [`digest_domain`] input.
+const DOMAIN_V1_TAG: &[u8] = b"sha256-rs domain v1\0";
+
+/// sha256 digest `input` on behalf of `domain`, as hex
+///
+/// Subsystems hashing the same bytes under different domains, such as `"myapp/session-id"` and
+/// `"myapp/cache-key"`, get unrelated digests, so one can't be passed off as the other. The
+/// domain is length-prefixed after a fixed tag, which also keeps these digests apart from the
+/// ones of [`digest_salted`]:
+///
+/// ```text
+/// sha256("sha256-rs domain v1\0" || len(domain) as u64 big endian || domain || input)
+/// ```
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::digest_domain;
+/// assert_ne!(digest_domain("myapp/session-id", "42"), digest_domain("myapp/cache-key", "42"));
+/// ```
+pub fn digest_domain<T: AsRef<[u8]>>(domain: &str, input: T) -> String {
+    let mut sha = Sha256::new_with_prefix(DOMAIN_V1_TAG);
+    sha.update((domain.len() as u64).to_be_bytes());
+    sha.update(domain);
+    sha.update(input);
+    lower_hex::encode(sha.finalize())
+}
+
 /// sha256 digest file
 ///
 /// # Examples
@@ -89,6 +323,233 @@ pub fn try_digest<D: TrySha256Digest>(input: D) -> Result<String, D::Error> {
     input.digest()
 }
 
+/// sha256 digest file, as set up by `options`
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{try_digest_with, Options, SymlinkPolicy};
+/// let options = Options::new().symlinks(SymlinkPolicy::Error);
+/// let val = try_digest_with("./foo.file", &options).unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1")
+/// ```
+pub fn try_digest_with<P: AsRef<Path>>(path: P, options: &Options) -> Result<String, Error> {
+    try_digest_with_progress(path, options, |_| {})
+}
+
+/// Like [`try_digest_with`], calling `progress` with the number of bytes hashed so far after
+/// every MiB, and once more when the whole file is done
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{try_digest_with_progress, Options};
+/// let mut done = 0;
+/// let val = try_digest_with_progress("./foo.file", &Options::new(), |n| done = n).unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// assert_eq!(done, 12);
+/// ```
+pub fn try_digest_with_progress<P, F>(
+    path: P,
+    options: &Options,
+    progress: F,
+) -> Result<String, Error>
+where
+    P: AsRef<Path>,
+    F: FnMut(u64),
+{
+    let path = path.as_ref();
+    if options.symlinks != SymlinkPolicy::Follow {
+        let meta =
+            fs::symlink_metadata(path).map_err(|e| Error::io(Operation::Metadata, path, e))?;
+        if meta.is_symlink() {
+            if options.symlinks == SymlinkPolicy::Error {
+                return Err(Error::Symlink(path.to_path_buf()));
+            }
+            let target =
+                fs::read_link(path).map_err(|e| Error::io(Operation::ReadLink, path, e))?;
+            return Ok(symlink_digest(&target));
+        }
+    }
+    let f = if options.direct_io {
+        let f = direct::open_direct(path).map_err(|e| Error::io(Operation::Open, path, e))?;
+        os::advise_sequential(&f);
+        f
+    } else {
+        open_file(path)?
+    };
+    let limit = options.max_bytes.unwrap_or(u64::MAX);
+    let too_large = || Error::TooLarge {
+        path: path.to_path_buf(),
+        limit,
+    };
+    let meta = f
+        .metadata()
+        .map_err(|e| Error::io(Operation::Metadata, path, e))?;
+    if options.max_bytes.is_some() && meta.len() > limit {
+        return Err(too_large());
+    }
+    let buffer_size = options.buffer_size_for(&meta);
+    let f = if options.direct_io {
+        FileInput::Direct(direct::DirectReader::new(f, buffer_size))
+    } else {
+        FileInput::Plain(f)
+    };
+    let mut throttle = Throttle::new(options.rate_limit);
+    let input = Progress::new(Throttled::new(f, &mut throttle), progress);
+    let mut input = input.take(limit.saturating_add(1));
+    let res = if options.pipelined && options.aligned_buffer {
+        let bufs = [(); 2].map(|()| direct::page_aligned_buf(buffer_size));
+        pipeline::calc_pipelined(&mut input, Sha256::new(), bufs)
+    } else if options.pipelined {
+        let bufs = [(); 2].map(|()| vec![0u8; buffer_size.max(1)]);
+        pipeline::calc_pipelined(&mut input, Sha256::new(), bufs)
+    } else if options.aligned_buffer {
+        let mut buf = direct::page_aligned_buf(buffer_size);
+        calc_raw_buf(&mut input, Sha256::new(), &mut buf)
+    } else {
+        calc_raw_sized(&mut input, Sha256::new(), buffer_size)
+    };
+    let (hash, _) = res.map_err(|(e, offset)| Error::read(path, offset, e))?;
+    if options.drop_page_cache {
+        os::drop_page_cache(input.get_ref().inner.get_ref().file());
+    }
+    if input.limit() == 0 {
+        return Err(too_large());
+    }
+    Ok(lower_hex::encode(hash))
+}
+
+/// sha256 digest file, also reporting how many bytes were read and how long it took
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_digest_ext;
+/// let report = try_digest_ext("./foo.file").unwrap();
+/// assert_eq!(report.hash.to_hex(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// assert_eq!(report.bytes_read, 12);
+/// ```
+pub fn try_digest_ext<P: AsRef<Path>>(path: P) -> Result<DigestReport, Error> {
+    let path = path.as_ref();
+    let start = Instant::now();
+    let f = open_file(path)?;
+    let (hash, bytes_read) =
+        calc_raw_offset(f, Sha256::new()).map_err(|(e, offset)| Error::read(path, offset, e))?;
+    Ok(DigestReport {
+        hash: hash.into(),
+        bytes_read,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// sha256 digest many files, keeping the result of every file
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_digest_all;
+/// use std::path::Path;
+/// let res = try_digest_all(["./foo.file", "./not_exist.file"]);
+/// assert_eq!(res[Path::new("./foo.file")].as_ref().unwrap(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// assert!(res[Path::new("./not_exist.file")].is_err());
+/// ```
+pub fn try_digest_all<I, P>(paths: I) -> BTreeMap<PathBuf, Result<String, Error>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    paths
+        .into_iter()
+        .map(|path| {
+            let path = path.as_ref();
+            (path.to_path_buf(), try_digest(path))
+        })
+        .collect()
+}
+
+/// sha256 digest `len` bytes of a file, starting at `offset`
+///
+/// Fails with an `io::ErrorKind::UnexpectedEof` error when the range goes past the end of the
+/// file.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, try_digest_range};
+/// let bytes = std::fs::read("./foo.file").unwrap();
+/// let val = try_digest_range("./foo.file", 2, 5).unwrap();
+/// assert_eq!(val, digest(&bytes[2..7]));
+/// ```
+pub fn try_digest_range<P: AsRef<Path>>(path: P, offset: u64, len: u64) -> Result<String, Error> {
+    let path = path.as_ref();
+    let mut f = open_file(path)?;
+    f.seek(SeekFrom::Start(offset))
+        .map_err(|e| Error::io(Operation::Seek, path, e))?;
+    let mut range = f.take(len);
+    let hash = calc_path(path, offset, &mut range, Sha256::new())?;
+    if range.limit() != 0 {
+        let e = io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "range goes past the end of the file",
+        );
+        return Err(Error::read(path, offset + len - range.limit(), e));
+    }
+    Ok(lower_hex::encode(hash))
+}
+
+/// Check a file against its expected digest, streaming it and comparing in constant time
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{verify_file, Sha256Hash};
+/// let expected: Sha256Hash = "433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1".parse().unwrap();
+/// assert!(verify_file("./foo.file", &expected).unwrap());
+/// ```
+pub fn verify_file<P: AsRef<Path>>(path: P, expected: &Sha256Hash) -> Result<bool, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    let hash = Sha256Hash::from(calc_file(path, f)?);
+    Ok(hash.ct_eq(expected))
+}
+
+/// Like [`verify_file`], failing fast without reading the file when its length isn't
+/// `expected_len`
+pub fn verify_file_len<P: AsRef<Path>>(
+    path: P,
+    expected: &Sha256Hash,
+    expected_len: u64,
+) -> Result<bool, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    if file_len(&f, path)? != expected_len {
+        return Ok(false);
+    }
+    let hash = Sha256Hash::from(calc_file(path, f)?);
+    Ok(hash.ct_eq(expected))
+}
+
+/// sha256 digest everything `reader` yields, with vectored reads
+///
+/// Each read scatters into several buffers at once (`readv`), so sources like sockets and pipes
+/// that have a lot of data ready hand it over in fewer calls. Readers without vectored read
+/// support are read one buffer at a time.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_digest_reader_vectored;
+/// let f = std::fs::File::open("./foo.file").unwrap();
+/// let val = try_digest_reader_vectored(f).unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// ```
+pub fn try_digest_reader_vectored<R: Read>(reader: R) -> io::Result<String> {
+    let (hash, _) = calc_raw_vectored(reader, Sha256::new(), VECTORED_BUFS, DEFAULT_BUFFER_SIZE)
+        .map_err(|(e, _)| e)?;
+    Ok(lower_hex::encode(hash))
+}
+
 /// sha256 digest bytes
 ///
 /// # Examples
@@ -126,17 +587,20 @@ pub trait Sha256Digest {
     fn digest(self) -> String;
 }
 
-#[async_trait::async_trait]
 pub trait TrySha256Digest {
     type Error: Debug;
 
     fn digest(self) -> Result<String, Self::Error>;
 
-    #[cfg(feature = "async")]
-    async fn async_digest(self) -> Result<String, Self::Error>;
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
+    fn async_digest(self) -> impl Future<Output = Result<String, Self::Error>> + Send;
 
-    #[cfg(feature = "native_openssl")]
-    async fn async_openssl_digest(self) -> Result<String, Self::Error>;
+    #[cfg(all(
+        feature = "async",
+        feature = "native_openssl",
+        not(target_arch = "wasm32")
+    ))]
+    fn async_openssl_digest(self) -> impl Future<Output = Result<String, Self::Error>> + Send;
 }
 
 impl<const N: usize> Sha256Digest for &[u8; N] {
@@ -193,43 +657,48 @@ impl Sha256Digest for &String {
     }
 }
 
-#[async_trait::async_trait]
 impl<P> TrySha256Digest for P
 where
     P: AsRef<Path> + Send,
 {
-    type Error = io::Error;
+    type Error = Error;
 
     fn digest(self) -> Result<String, Self::Error> {
-        let f = fs::File::open(self)?;
-        let reader = BufReader::new(f);
-        let sha = Sha256::new();
-        calc(reader, sha)
+        let path = self.as_ref();
+        let f = open_file(path)?;
+        Ok(lower_hex::encode(calc_file(path, f)?))
     }
 
-    #[cfg(feature = "async")]
+    #[cfg(all(feature = "async", not(target_arch = "wasm32")))]
     async fn async_digest(self) -> Result<String, Self::Error> {
-        let f = tokio::fs::File::open(self).await?;
-        let reader = tokio::io::BufReader::new(f);
         let sha = Sha256::new();
-        async_calc(reader, sha).await
+        Ok(lower_hex::encode(
+            async_calc_path(self.as_ref(), sha).await?,
+        ))
     }
 
-    #[cfg(all(feature = "async", feature = "native_openssl"))]
+    #[cfg(all(
+        feature = "async",
+        feature = "native_openssl",
+        not(target_arch = "wasm32")
+    ))]
     async fn async_openssl_digest(self) -> Result<String, Self::Error> {
-        let f = tokio::fs::File::open(self).await?;
-        let reader = tokio::io::BufReader::new(f);
         let sha = OpenSslSha256::new();
-        async_calc(reader, sha).await
+        Ok(lower_hex::encode(
+            async_calc_path(self.as_ref(), sha).await?,
+        ))
     }
 }
 
 fn __digest__(data: &[u8]) -> String {
-    hex::encode(Sha256::digest(data))
+    lower_hex::encode(Sha256::digest(data))
 }
 
 trait CalculatorInput {
     fn read_inner(&mut self, buf: &mut [u8]) -> std::io::Result<usize>;
+
+    /// Scatter read, sources without `readv` support only fill the first buffer.
+    fn read_vectored_inner(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize>;
 }
 
 impl<T> CalculatorInput for T
@@ -239,6 +708,10 @@ where
     fn read_inner(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.read(buf)
     }
+
+    fn read_vectored_inner(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.read_vectored(bufs)
+    }
 }
 
 pub trait CalculatorSelector {
@@ -259,19 +732,222 @@ impl CalculatorSelector for Sha256 {
     }
 }
 
-fn calc<I, S>(mut input: I, mut selector: S) -> io::Result<String>
+/// Size of the read buffer of the streaming loops, unless set by [`Options::buffer_size`] or
+/// picked from the input.
+pub const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Number of buffers of [`try_digest_reader_vectored`].
+const VECTORED_BUFS: usize = 4;
+
+#[cfg(test)]
+fn calc<I, S>(input: I, selector: S) -> io::Result<String>
 where
     I: CalculatorInput,
     S: CalculatorSelector,
 {
-    let mut buf = [0u8; 1024];
+    let hash = calc_raw(input, selector)?;
+    Ok(lower_hex::encode(hash))
+}
+
+#[cfg(any(test, feature = "tar", feature = "zip"))]
+fn calc_raw<I, S>(input: I, selector: S) -> io::Result<S::FinishType>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector,
+{
+    calc_raw_offset(input, selector)
+        .map(|(hash, _)| hash)
+        .map_err(|(e, _)| e)
+}
+
+/// Like `calc_raw`, also giving back how many bytes were hashed, up to a read error if any.
+fn calc_raw_offset<I, S>(input: I, selector: S) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector,
+{
+    calc_raw_sized(input, selector, DEFAULT_BUFFER_SIZE)
+}
+
+/// Like `calc_raw_offset`, reading `buffer_size` bytes at a time.
+fn calc_raw_sized<I, S>(
+    input: I,
+    selector: S,
+    buffer_size: usize,
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector,
+{
+    calc_raw_buf(input, selector, &mut vec![0u8; buffer_size.max(1)])
+}
+
+/// Like `calc_raw_offset`, reading into `buf`.
+fn calc_raw_buf<I, S>(
+    mut input: I,
+    mut selector: S,
+    buf: &mut [u8],
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector,
+{
+    let mut offset = 0;
     loop {
-        let len = input.read_inner(&mut buf)?;
+        let len = input.read_inner(buf).map_err(|e| (e, offset))?;
         if len == 0 {
             break;
         }
         selector.update_inner(&buf[0..len]);
+        offset += len as u64;
+    }
+    Ok((selector.finish_inner(), offset))
+}
+
+/// Like `calc_raw_sized`, reading into `bufs` buffers of `buffer_size` bytes with one vectored
+/// read at a time.
+fn calc_raw_vectored<I, S>(
+    mut input: I,
+    mut selector: S,
+    bufs: usize,
+    buffer_size: usize,
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector,
+{
+    let mut bufs = vec![vec![0u8; buffer_size.max(1)]; bufs.max(1)];
+    let mut offset = 0;
+    loop {
+        let mut slices = bufs
+            .iter_mut()
+            .map(|buf| IoSliceMut::new(buf))
+            .collect::<Vec<_>>();
+        let mut len = input
+            .read_vectored_inner(&mut slices)
+            .map_err(|e| (e, offset))?;
+        if len == 0 {
+            break;
+        }
+        offset += len as u64;
+        for buf in &bufs {
+            let n = len.min(buf.len());
+            selector.update_inner(&buf[..n]);
+            len -= n;
+            if len == 0 {
+                break;
+            }
+        }
+    }
+    Ok((selector.finish_inner(), offset))
+}
+
+/// Hash `input`, read from `path` starting at byte `offset`.
+fn calc_path<I, S>(path: &Path, offset: u64, input: I, selector: S) -> Result<S::FinishType, Error>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector,
+{
+    calc_raw_offset(input, selector)
+        .map(|(hash, _)| hash)
+        .map_err(|(e, n)| Error::read(path, offset + n, e))
+}
+
+/// Regular files smaller than this are read with a single call and hashed in one go.
+const SMALL_FILE: u64 = 256 * 1024;
+
+/// Hash a whole file, skipping the chunk loop for files smaller than `SMALL_FILE`.
+fn calc_file_raw(mut file: fs::File) -> Result<Output<Sha256>, (io::Error, u64)> {
+    match file.metadata() {
+        Ok(meta) if meta.is_file() && meta.len() < SMALL_FILE => {
+            // One byte more than the length, so that EOF is hit without growing the buffer.
+            let mut buf = Vec::with_capacity(meta.len() as usize + 1);
+            if let Err(e) = file.read_to_end(&mut buf) {
+                return Err((e, buf.len() as u64));
+            }
+            Ok(Sha256::digest(&buf))
+        }
+        _ => calc_raw_offset(file, Sha256::new()).map(|(hash, _)| hash),
     }
-    let hash = selector.finish_inner();
-    Ok(hex::encode(hash))
+}
+
+/// Like `calc_file_raw`, for the file at `path`.
+fn calc_file(path: &Path, file: fs::File) -> Result<Output<Sha256>, Error> {
+    calc_file_raw(file).map_err(|(e, offset)| Error::read(path, offset, e))
+}
+
+/// File read either directly or through [`direct::DirectReader`].
+enum FileInput {
+    Plain(fs::File),
+    Direct(direct::DirectReader),
+}
+
+impl FileInput {
+    fn file(&self) -> &fs::File {
+        match self {
+            FileInput::Plain(f) => f,
+            FileInput::Direct(r) => r.get_ref(),
+        }
+    }
+}
+
+impl Read for FileInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            FileInput::Plain(f) => f.read(buf),
+            FileInput::Direct(r) => r.read(buf),
+        }
+    }
+}
+
+/// Reader calling back with the number of bytes read so far, every `PROGRESS_STEP` bytes and
+/// at the end of the input.
+struct Progress<R, F> {
+    inner: R,
+    progress: F,
+    done: u64,
+    reported: u64,
+}
+
+const PROGRESS_STEP: u64 = 1 << 20;
+
+impl<R, F> Progress<R, F> {
+    fn new(inner: R, progress: F) -> Self {
+        Progress {
+            inner,
+            progress,
+            done: 0,
+            reported: 0,
+        }
+    }
+}
+
+impl<R: Read, F: FnMut(u64)> Read for Progress<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.done += n as u64;
+        if n == 0 || self.done - self.reported >= PROGRESS_STEP {
+            self.reported = self.done;
+            (self.progress)(self.done);
+        }
+        Ok(n)
+    }
+}
+
+/// What [`SymlinkPolicy::HashTarget`] hashes.
+fn symlink_digest(target: &Path) -> String {
+    __digest__(&dir::encode_rel_path(target))
+}
+
+/// Open a file to hash it.
+fn open_file(path: &Path) -> Result<fs::File, Error> {
+    let f = os::open_sequential(path).map_err(|e| Error::io(Operation::Open, path, e))?;
+    os::advise_sequential(&f);
+    Ok(f)
+}
+
+fn file_len(f: &fs::File, path: &Path) -> Result<u64, Error> {
+    f.metadata()
+        .map(|meta| meta.len())
+        .map_err(|e| Error::io(Operation::Metadata, path, e))
 }
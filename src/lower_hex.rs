@@ -0,0 +1,34 @@
+/// Every byte as its two lowercase hex digits.
+const TABLE: [[u8; 2]; 256] = table();
+
+const fn table() -> [[u8; 2]; 256] {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut table = [[0; 2]; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = [DIGITS[i >> 4], DIGITS[i & 0xf]];
+        i += 1;
+    }
+    table
+}
+
+/// Lowercase hex of `bytes` written into `out`, which must be twice as long.
+pub(crate) fn encode_to_slice(bytes: &[u8], out: &mut [u8]) {
+    assert_eq!(
+        out.len(),
+        bytes.len() * 2,
+        "hex output has the wrong length"
+    );
+    for (b, pair) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+        pair.copy_from_slice(&TABLE[*b as usize]);
+    }
+}
+
+/// Lowercase hex, a drop-in for `hex::encode` that fills the `String` in one go.
+pub(crate) fn encode<T: AsRef<[u8]>>(data: T) -> String {
+    let bytes = data.as_ref();
+    let mut out = vec![0; bytes.len() * 2];
+    encode_to_slice(bytes, &mut out);
+    // Safety: the table only holds ASCII hex digits.
+    unsafe { String::from_utf8_unchecked(out) }
+}
@@ -0,0 +1,469 @@
+use crate::dir::{encode_rel_path, Walk};
+use crate::{calc_file, calc_file_raw, DirOptions, Error, Operation, Sha256Hash};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A list of file digests, as found in `SHA256SUMS` files
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::manifest::Manifest;
+/// let manifest = Manifest::from_paths(["./foo.file"]).unwrap();
+/// assert_eq!(manifest.to_string(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1  ./foo.file\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub path: PathBuf,
+    pub hash: Sha256Hash,
+}
+
+impl Manifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push<P: Into<PathBuf>>(&mut self, path: P, hash: Sha256Hash) {
+        self.entries.push(ManifestEntry {
+            path: path.into(),
+            hash,
+        });
+    }
+
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// Parse a manifest in the GNU coreutils `sha256sum` format or the BSD tag format.
+    ///
+    /// The format is detected line by line. GNU text (`<hex>  <path>`) and binary
+    /// (`<hex> *<path>`) lines are accepted, as well as BSD (`SHA256 (<path>) = <hex>`) lines,
+    /// either one escaped and starting with a `\`. Empty lines are skipped, any other malformed
+    /// line is an error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sha256::manifest::Manifest;
+    /// let manifest = Manifest::parse(b"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1  ./foo.file\n").unwrap();
+    /// assert_eq!(manifest.entries()[0].path.to_str(), Some("./foo.file"));
+    /// ```
+    pub fn parse(input: &[u8]) -> Result<Manifest, ParseError> {
+        let mut manifest = Manifest::new();
+        for (i, line) in input.split(|&b| b == b'\n').enumerate() {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.is_empty() {
+                continue;
+            }
+            let entry = parse_line(line).ok_or(ParseError { line: i + 1 })?;
+            manifest.entries.push(entry);
+        }
+        Ok(manifest)
+    }
+
+    /// Hash `paths`, recording each one as given.
+    pub fn from_paths<I, P>(paths: I) -> Result<Manifest, Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut manifest = Manifest::new();
+        for path in paths {
+            let path = path.as_ref();
+            manifest.push(path, hash_file(path)?);
+        }
+        Ok(manifest)
+    }
+
+    /// Hash every file under `root`, recording paths relative to `root`.
+    pub fn from_dir<P: AsRef<Path>>(root: P) -> Result<Manifest, Error> {
+        Self::from_dir_with(root, &DirOptions::new())
+    }
+
+    /// Hash the files under `root` selected by `options`, recording paths relative to `root`.
+    pub fn from_dir_with<P: AsRef<Path>>(root: P, options: &DirOptions) -> Result<Manifest, Error> {
+        let mut manifest = Manifest::new();
+        for entry in Walk::with_options(root.as_ref(), options.clone()) {
+            let entry = entry?;
+            manifest.push(entry.rel, hash_file(&entry.path)?);
+        }
+        Ok(manifest)
+    }
+
+    /// Compare `self`, the old state, with `new`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sha256::manifest::Manifest;
+    /// use sha256::Sha256Hash;
+    /// let mut old = Manifest::new();
+    /// old.push("a", Sha256Hash::new([0; 32]));
+    /// let mut new = Manifest::new();
+    /// new.push("a", Sha256Hash::new([1; 32]));
+    /// new.push("b", Sha256Hash::new([2; 32]));
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.added.len(), 1);
+    /// assert_eq!(diff.changed[0].new, Sha256Hash::new([1; 32]));
+    /// ```
+    pub fn diff(&self, new: &Manifest) -> ManifestDiff {
+        let old = self.by_path();
+        let new = new.by_path();
+        let mut diff = ManifestDiff::default();
+        for (&path, &old_hash) in &old {
+            match new.get(path) {
+                None => diff.removed.push(ManifestEntry {
+                    path: path.to_path_buf(),
+                    hash: old_hash,
+                }),
+                Some(&new_hash) if new_hash != old_hash => diff.changed.push(ChangedEntry {
+                    path: path.to_path_buf(),
+                    old: old_hash,
+                    new: new_hash,
+                }),
+                Some(_) => {}
+            }
+        }
+        for (&path, &hash) in &new {
+            if !old.contains_key(path) {
+                diff.added.push(ManifestEntry {
+                    path: path.to_path_buf(),
+                    hash,
+                });
+            }
+        }
+        diff
+    }
+
+    /// Compare `self` with the files currently under `root`.
+    pub fn diff_dir<P: AsRef<Path>>(&self, root: P) -> Result<ManifestDiff, Error> {
+        self.diff_dir_with(root, &DirOptions::new())
+    }
+
+    /// Compare `self` with the files under `root` currently selected by `options`.
+    pub fn diff_dir_with<P: AsRef<Path>>(
+        &self,
+        root: P,
+        options: &DirOptions,
+    ) -> Result<ManifestDiff, Error> {
+        Ok(self.diff(&Manifest::from_dir_with(root, options)?))
+    }
+
+    /// Later entries win over earlier ones with the same path.
+    fn by_path(&self) -> BTreeMap<&Path, Sha256Hash> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.path.as_path(), entry.hash))
+            .collect()
+    }
+
+    /// Write the manifest in the GNU coreutils `sha256sum` format, `<hex>  <path>` per line.
+    ///
+    /// Like `sha256sum`, a path containing `\`, a newline or a carriage return is escaped and its
+    /// line is prefixed with a `\`.
+    pub fn write_to<W: Write>(&self, w: W) -> io::Result<()> {
+        self.write_format(w, ManifestFormat::Gnu)
+    }
+
+    /// Write the manifest in the given format, escaping paths like [`Manifest::write_to`].
+    pub fn write_format<W: Write>(&self, mut w: W, format: ManifestFormat) -> io::Result<()> {
+        for entry in &self.entries {
+            let path = encode_rel_path(&entry.path);
+            let escaped = escape_path(&path);
+            if escaped.len() != path.len() {
+                w.write_all(b"\\")?;
+            }
+            match format {
+                ManifestFormat::Gnu => {
+                    write!(w, "{}  ", entry.hash)?;
+                    w.write_all(&escaped)?;
+                }
+                ManifestFormat::Bsd => {
+                    w.write_all(b"SHA256 (")?;
+                    w.write_all(&escaped)?;
+                    write!(w, ") = {}", entry.hash)?;
+                }
+            }
+            w.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Differences between two manifests, each list sorted by path
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestDiff {
+    pub added: Vec<ManifestEntry>,
+    pub removed: Vec<ManifestEntry>,
+    pub changed: Vec<ChangedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedEntry {
+    pub path: PathBuf,
+    pub old: Sha256Hash,
+    pub new: Sha256Hash,
+}
+
+impl ManifestDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Line format of a manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// `<hex>  <path>`, as written by `sha256sum`
+    Gnu,
+    /// `SHA256 (<path>) = <hex>`, as written by `sha256sum --tag` and BSD `sha256`
+    Bsd,
+}
+
+impl ManifestFormat {
+    /// Detect the format of a manifest from its first non-empty line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sha256::manifest::ManifestFormat;
+    /// let input = b"SHA256 (foo.file) = 433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1\n";
+    /// assert_eq!(ManifestFormat::detect(input), Some(ManifestFormat::Bsd));
+    /// ```
+    pub fn detect(input: &[u8]) -> Option<ManifestFormat> {
+        let line = input
+            .split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+            .find(|line| !line.is_empty())?;
+        let line = line.strip_prefix(b"\\").unwrap_or(line);
+        if parse_bsd_line(line, false).is_some() {
+            Some(ManifestFormat::Bsd)
+        } else if parse_gnu_line(line, false).is_some() {
+            Some(ManifestFormat::Gnu)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = vec![];
+        self.write_to(&mut out).map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&out))
+    }
+}
+
+/// A manifest line that couldn't be parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based line number
+    pub line: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "improperly formatted sha256 checksum line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_line(line: &[u8]) -> Option<ManifestEntry> {
+    let (escaped, line) = match line.strip_prefix(b"\\") {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    parse_bsd_line(line, escaped).or_else(|| parse_gnu_line(line, escaped))
+}
+
+fn parse_gnu_line(line: &[u8], escaped: bool) -> Option<ManifestEntry> {
+    if line.len() < 66 || line[64] != b' ' || !matches!(line[65], b' ' | b'*') {
+        return None;
+    }
+    entry(&line[66..], &line[..64], escaped)
+}
+
+fn parse_bsd_line(line: &[u8], escaped: bool) -> Option<ManifestEntry> {
+    let rest = line.strip_prefix(b"SHA256 (")?;
+    if rest.len() < 68 || &rest[rest.len() - 68..rest.len() - 64] != b") = " {
+        return None;
+    }
+    let (path, hash) = rest.split_at(rest.len() - 68);
+    entry(path, &hash[4..], escaped)
+}
+
+fn entry(path: &[u8], hash: &[u8], escaped: bool) -> Option<ManifestEntry> {
+    let hash = std::str::from_utf8(hash).ok()?.parse().ok()?;
+    if path.is_empty() {
+        return None;
+    }
+    let path = if escaped {
+        unescape_path(path)?
+    } else {
+        path.to_vec()
+    };
+    Some(ManifestEntry {
+        path: decode_path(path)?,
+        hash,
+    })
+}
+
+fn unescape_path(path: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(path.len());
+    let mut bytes = path.iter();
+    while let Some(&b) = bytes.next() {
+        if b != b'\\' {
+            out.push(b);
+            continue;
+        }
+        match bytes.next()? {
+            b'\\' => out.push(b'\\'),
+            b'n' => out.push(b'\n'),
+            b'r' => out.push(b'\r'),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+#[cfg(unix)]
+fn decode_path(path: Vec<u8>) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+
+    Some(PathBuf::from(std::ffi::OsString::from_vec(path)))
+}
+
+#[cfg(not(unix))]
+fn decode_path(path: Vec<u8>) -> Option<PathBuf> {
+    String::from_utf8(path).ok().map(PathBuf::from)
+}
+
+/// Check every entry of `manifest` against the files under `base_dir`, like `sha256sum --check`
+///
+/// Relative manifest paths are resolved against `base_dir`, absolute ones are used as is.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::manifest::{verify_manifest, Manifest};
+/// let manifest = Manifest::parse(b"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1  foo.file\n").unwrap();
+/// let report = verify_manifest(&manifest, ".");
+/// assert!(report.is_ok());
+/// assert_eq!(report.to_string(), "foo.file: OK\n");
+/// ```
+pub fn verify_manifest<P: AsRef<Path>>(manifest: &Manifest, base_dir: P) -> VerifyReport {
+    let base_dir = base_dir.as_ref();
+    let entries = manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            let file = fs::File::open(base_dir.join(&entry.path));
+            let status = match file.and_then(|file| calc_file_raw(file).map_err(|(e, _)| e)) {
+                Ok(hash) if Sha256Hash::from(hash) == entry.hash => VerifyStatus::Ok,
+                Ok(_) => VerifyStatus::Failed,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => VerifyStatus::Missing,
+                Err(e) => VerifyStatus::Unreadable(e.kind()),
+            };
+            VerifyEntry {
+                path: entry.path.clone(),
+                status,
+            }
+        })
+        .collect();
+    VerifyReport { entries }
+}
+
+/// Outcome of [`verify_manifest`], one entry per manifest line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    pub entries: Vec<VerifyEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyEntry {
+    pub path: PathBuf,
+    pub status: VerifyStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyStatus {
+    /// The file matches its digest.
+    Ok,
+    /// The file content doesn't match its digest.
+    Failed,
+    /// The file doesn't exist.
+    Missing,
+    /// The file exists but couldn't be opened or read, e.g. for lack of permission or because
+    /// it is a directory.
+    Unreadable(io::ErrorKind),
+}
+
+impl VerifyReport {
+    /// Whether every file matched.
+    pub fn is_ok(&self) -> bool {
+        self.entries
+            .iter()
+            .all(|entry| entry.status == VerifyStatus::Ok)
+    }
+
+    /// Number of files whose content didn't match.
+    pub fn failed(&self) -> usize {
+        self.count(|status| status == VerifyStatus::Failed)
+    }
+
+    /// Number of files that don't exist.
+    pub fn missing(&self) -> usize {
+        self.count(|status| status == VerifyStatus::Missing)
+    }
+
+    /// Number of files that exist but couldn't be read.
+    pub fn unreadable(&self) -> usize {
+        self.count(|status| matches!(status, VerifyStatus::Unreadable(_)))
+    }
+
+    fn count(&self, f: impl Fn(VerifyStatus) -> bool) -> usize {
+        self.entries.iter().filter(|entry| f(entry.status)).count()
+    }
+}
+
+/// `sha256sum --check` style output, `<path>: OK` per line.
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let status = match entry.status {
+                VerifyStatus::Ok => "OK",
+                VerifyStatus::Failed => "FAILED",
+                VerifyStatus::Missing | VerifyStatus::Unreadable(_) => "FAILED open or read",
+            };
+            writeln!(f, "{}: {}", entry.path.display(), status)?;
+        }
+        Ok(())
+    }
+}
+
+fn escape_path(path: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(path.len());
+    for &b in path {
+        match b {
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b => out.push(b),
+        }
+    }
+    out
+}
+
+fn hash_file(path: &Path) -> Result<Sha256Hash, Error> {
+    let file = fs::File::open(path).map_err(|e| Error::io(Operation::Open, path, e))?;
+    Ok(calc_file(path, file)?.into())
+}
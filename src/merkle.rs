@@ -0,0 +1,400 @@
+//! SHA-256 Merkle trees
+//!
+//! Leaves are hashed as `sha256(leaf_prefix || leaf)` and inner nodes as
+//! `sha256(node_prefix || left || right)`. When a level has an odd number of nodes, the last one
+//! is moved up a level as is, instead of being paired with itself. The prefixes default to
+//! `0x00` and `0x01`, which keeps a leaf from being passed off as an inner node.
+//!
+//! With the default prefixes, trees, inclusion proofs and consistency proofs are those of
+//! Certificate Transparency logs ([RFC 6962](https://www.rfc-editor.org/rfc/rfc6962)).
+//!
+//! ```rust
+//! use sha256::merkle::MerkleBuilder;
+//! let builder = MerkleBuilder::new();
+//! let tree = builder.build(["a", "b", "c"]);
+//! let ab = builder.hash_node(&builder.hash_leaf("a"), &builder.hash_leaf("b"));
+//! assert_eq!(tree.root(), builder.hash_node(&ab, &builder.hash_leaf("c")));
+//! ```
+//!
+//! Proofs show that a leaf is in a tree knowing only its root:
+//!
+//! ```rust
+//! use sha256::merkle::{MerkleBuilder, Proof};
+//! let tree = MerkleBuilder::new().build(["a", "b", "c"]);
+//! let bytes = tree.prove(2).unwrap().to_bytes();
+//! let proof = Proof::from_bytes(&bytes).unwrap();
+//! assert!(proof.verify(&tree.root(), "c"));
+//! assert!(!proof.verify(&tree.root(), "d"));
+//! ```
+
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+
+/// How leaves and inner nodes are hashed, to build [`MerkleTree`]s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleBuilder {
+    leaf_prefix: Vec<u8>,
+    node_prefix: Vec<u8>,
+}
+
+impl Default for MerkleBuilder {
+    fn default() -> Self {
+        MerkleBuilder {
+            leaf_prefix: vec![0x00],
+            node_prefix: vec![0x01],
+        }
+    }
+}
+
+impl MerkleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The hashing of RFC 6962, the same as the default.
+    pub fn rfc6962() -> Self {
+        Self::default()
+    }
+
+    /// Bytes hashed before each leaf, `0x00` by default. Can be empty.
+    pub fn leaf_prefix<T: Into<Vec<u8>>>(mut self, prefix: T) -> Self {
+        self.leaf_prefix = prefix.into();
+        self
+    }
+
+    /// Bytes hashed before each pair of children, `0x01` by default. Can be empty.
+    pub fn node_prefix<T: Into<Vec<u8>>>(mut self, prefix: T) -> Self {
+        self.node_prefix = prefix.into();
+        self
+    }
+
+    pub fn hash_leaf<T: AsRef<[u8]>>(&self, leaf: T) -> Sha256Hash {
+        Sha256::new_with_prefix(&self.leaf_prefix)
+            .chain_update(leaf)
+            .finalize()
+            .into()
+    }
+
+    pub fn hash_node(&self, left: &Sha256Hash, right: &Sha256Hash) -> Sha256Hash {
+        Sha256::new_with_prefix(&self.node_prefix)
+            .chain_update(left)
+            .chain_update(right)
+            .finalize()
+            .into()
+    }
+
+    /// Build the tree of `leaves`, hashing each of them with [`hash_leaf`](Self::hash_leaf).
+    pub fn build<I>(&self, leaves: I) -> MerkleTree
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.build_from_digests(leaves.into_iter().map(|leaf| self.hash_leaf(leaf)))
+    }
+
+    /// Build the tree of leaves already hashed, which are used as is.
+    pub fn build_from_digests<I: IntoIterator<Item = Sha256Hash>>(&self, leaves: I) -> MerkleTree {
+        let mut levels = vec![leaves.into_iter().collect::<Vec<_>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => self.hash_node(left, right),
+                    [lone] => *lone,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree {
+            builder: self.clone(),
+            levels,
+        }
+    }
+}
+
+/// A Merkle tree, with all its inner nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleTree {
+    builder: MerkleBuilder,
+    /// Leaf hashes first, up to the root.
+    levels: Vec<Vec<Sha256Hash>>,
+}
+
+impl MerkleTree {
+    /// The root hash, the sha256 of nothing for a tree without leaves.
+    pub fn root(&self) -> Sha256Hash {
+        match self.levels.last().and_then(|level| level.first()) {
+            Some(root) => *root,
+            None => Sha256::digest([]).into(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The hash of leaf `index`.
+    pub fn leaf(&self, index: usize) -> Option<Sha256Hash> {
+        self.levels[0].get(index).copied()
+    }
+
+    pub fn builder(&self) -> &MerkleBuilder {
+        &self.builder
+    }
+
+    /// The proof that leaf `index` is in the tree, `None` if there is no such leaf.
+    pub fn prove(&self, index: usize) -> Option<Proof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(i ^ 1) {
+                path.push(*sibling);
+            }
+            i >>= 1;
+        }
+        Some(Proof {
+            index: index as u64,
+            tree_size: self.len() as u64,
+            path,
+        })
+    }
+
+    /// The proof that the tree of the first `old_size` leaves is a prefix of this one, `None`
+    /// if `old_size` is 0 or more than the number of leaves.
+    pub fn prove_consistency(&self, old_size: usize) -> Option<ConsistencyProof> {
+        if old_size == 0 || old_size > self.len() {
+            return None;
+        }
+        let mut path = Vec::new();
+        self.subproof(old_size, 0, self.len(), true, &mut path);
+        Some(ConsistencyProof {
+            old_size: old_size as u64,
+            new_size: self.len() as u64,
+            path,
+        })
+    }
+
+    /// `SUBPROOF(m, D[lo:hi], b)` of RFC 6962.
+    fn subproof(&self, m: usize, lo: usize, hi: usize, b: bool, path: &mut Vec<Sha256Hash>) {
+        if m == hi - lo {
+            if !b {
+                path.push(self.node(lo, hi));
+            }
+            return;
+        }
+        // The largest power of 2 smaller than the number of leaves.
+        let k = 1 << (usize::BITS - 1 - (hi - lo - 1).leading_zeros());
+        if m <= k {
+            self.subproof(m, lo, lo + k, b, path);
+            path.push(self.node(lo + k, hi));
+        } else {
+            self.subproof(m - k, lo + k, hi, false, path);
+            path.push(self.node(lo, lo + k));
+        }
+    }
+
+    /// The node over leaves `lo..hi`, `lo` being a multiple of the smallest power of 2 at
+    /// least `hi - lo`, and `hi` a multiple of it too or the number of leaves.
+    fn node(&self, lo: usize, hi: usize) -> Sha256Hash {
+        let level = (hi - lo).next_power_of_two().trailing_zeros() as usize;
+        self.levels[level][lo >> level]
+    }
+}
+
+/// The hashes needed to get from a leaf to the root of a [`MerkleTree`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub index: u64,
+    /// Number of leaves of the tree.
+    pub tree_size: u64,
+    /// The siblings on the way up from the leaf, bottom first.
+    pub path: Vec<Sha256Hash>,
+}
+
+impl Proof {
+    /// Whether `leaf` is leaf [`index`](Proof::index) of the tree with this `root`, built by
+    /// the default [`MerkleBuilder`].
+    pub fn verify<T: AsRef<[u8]>>(&self, root: &Sha256Hash, leaf: T) -> bool {
+        let builder = MerkleBuilder::default();
+        self.verify_digest(&builder, root, &builder.hash_leaf(leaf))
+    }
+
+    /// Whether the leaf hashed to `leaf` is leaf [`index`](Proof::index) of the tree with this
+    /// `root`, built by `builder`.
+    pub fn verify_digest(
+        &self,
+        builder: &MerkleBuilder,
+        root: &Sha256Hash,
+        leaf: &Sha256Hash,
+    ) -> bool {
+        match self.root_from(builder, leaf) {
+            Some(computed) => computed.ct_eq(root),
+            None => false,
+        }
+    }
+
+    /// The root reached from `leaf` following the path, `None` if the path doesn't fit the
+    /// index and tree size.
+    fn root_from(&self, builder: &MerkleBuilder, leaf: &Sha256Hash) -> Option<Sha256Hash> {
+        if self.index >= self.tree_size {
+            return None;
+        }
+        let mut index = self.index;
+        let mut last = self.tree_size - 1;
+        let mut hash = *leaf;
+        for sibling in &self.path {
+            if last == 0 {
+                return None;
+            }
+            if index & 1 == 1 || index == last {
+                hash = builder.hash_node(sibling, &hash);
+                // The node was moved up as is on the levels where it was the odd one out.
+                while index & 1 == 0 && index != 0 {
+                    index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                hash = builder.hash_node(&hash, sibling);
+            }
+            index >>= 1;
+            last >>= 1;
+        }
+        (last == 0).then_some(hash)
+    }
+
+    /// The index and tree size as 64-bit big endian integers, followed by the path.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + 32 * self.path.len());
+        bytes.extend_from_slice(&self.index.to_be_bytes());
+        bytes.extend_from_slice(&self.tree_size.to_be_bytes());
+        for hash in &self.path {
+            bytes.extend_from_slice(hash.as_bytes());
+        }
+        bytes
+    }
+
+    /// Read back what [`to_bytes`](Proof::to_bytes) wrote, `None` if it's not the right length.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Proof> {
+        if bytes.len() < 16 || !(bytes.len() - 16).is_multiple_of(32) {
+            return None;
+        }
+        let (header, path) = bytes.split_at(16);
+        Some(Proof {
+            index: u64::from_be_bytes(header[..8].try_into().unwrap()),
+            tree_size: u64::from_be_bytes(header[8..].try_into().unwrap()),
+            path: path
+                .chunks(32)
+                .map(|hash| Sha256Hash::new(hash.try_into().unwrap()))
+                .collect(),
+        })
+    }
+}
+
+/// The hashes needed to check that a [`MerkleTree`] only appended leaves to an older version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyProof {
+    /// Number of leaves of the older tree.
+    pub old_size: u64,
+    /// Number of leaves of the newer tree.
+    pub new_size: u64,
+    pub path: Vec<Sha256Hash>,
+}
+
+impl ConsistencyProof {
+    /// Whether the tree with `new_root` starts with the leaves of the tree with `old_root`,
+    /// both built by the default [`MerkleBuilder`].
+    pub fn verify(&self, old_root: &Sha256Hash, new_root: &Sha256Hash) -> bool {
+        self.verify_with(&MerkleBuilder::default(), old_root, new_root)
+    }
+
+    /// Like [`verify`](ConsistencyProof::verify), for trees built by `builder`.
+    pub fn verify_with(
+        &self,
+        builder: &MerkleBuilder,
+        old_root: &Sha256Hash,
+        new_root: &Sha256Hash,
+    ) -> bool {
+        match self.roots_from(builder, old_root) {
+            Some((old, new)) => old.ct_eq(old_root) && new.ct_eq(new_root),
+            None => false,
+        }
+    }
+
+    /// The old and new roots computed from the path, following RFC 9162 section 2.1.4.2.
+    fn roots_from(
+        &self,
+        builder: &MerkleBuilder,
+        old_root: &Sha256Hash,
+    ) -> Option<(Sha256Hash, Sha256Hash)> {
+        if self.old_size == 0 || self.old_size > self.new_size {
+            return None;
+        }
+        if self.old_size == self.new_size {
+            return self.path.is_empty().then_some((*old_root, *old_root));
+        }
+        if self.path.is_empty() {
+            return None;
+        }
+        let mut path = self.path.iter();
+        let first = if self.old_size.is_power_of_two() {
+            *old_root
+        } else {
+            *path.next()?
+        };
+        let mut old_index = self.old_size - 1;
+        let mut last = self.new_size - 1;
+        while old_index & 1 == 1 {
+            old_index >>= 1;
+            last >>= 1;
+        }
+        let (mut old, mut new) = (first, first);
+        for hash in path {
+            if last == 0 {
+                return None;
+            }
+            if old_index & 1 == 1 || old_index == last {
+                old = builder.hash_node(hash, &old);
+                new = builder.hash_node(hash, &new);
+                while old_index & 1 == 0 && old_index != 0 {
+                    old_index >>= 1;
+                    last >>= 1;
+                }
+            } else {
+                new = builder.hash_node(&new, hash);
+            }
+            old_index >>= 1;
+            last >>= 1;
+        }
+        (last == 0).then_some((old, new))
+    }
+
+    /// The old and new sizes as 64-bit big endian integers, followed by the path.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        Proof {
+            index: self.old_size,
+            tree_size: self.new_size,
+            path: self.path.clone(),
+        }
+        .to_bytes()
+    }
+
+    /// Read back what [`to_bytes`](ConsistencyProof::to_bytes) wrote, `None` if it's not the
+    /// right length.
+    pub fn from_bytes(bytes: &[u8]) -> Option<ConsistencyProof> {
+        let proof = Proof::from_bytes(bytes)?;
+        Some(ConsistencyProof {
+            old_size: proof.index,
+            new_size: proof.tree_size,
+            path: proof.path,
+        })
+    }
+}
@@ -0,0 +1,96 @@
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Most tags whose midstate [`tagged_digest`] keeps per thread.
+const TAG_CACHE_SIZE: usize = 64;
+
+thread_local! {
+    static TAG_CACHE: RefCell<HashMap<Vec<u8>, Sha256Midstate>> = RefCell::new(HashMap::new());
+}
+
+/// The hashing state after a fixed prefix, to hash many messages sharing it
+///
+/// The full blocks of the prefix are compressed once, and each
+/// [`finalize_with_suffix`](Sha256Midstate::finalize_with_suffix) only compresses what's left
+/// of the prefix plus the suffix. Pad the prefix to a multiple of 64 bytes to skip it entirely.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, Sha256Midstate};
+/// let midstate = Sha256Midstate::from_prefix("block header ");
+/// let hash = midstate.finalize_with_suffix(42u64.to_le_bytes());
+/// let mut message = b"block header ".to_vec();
+/// message.extend_from_slice(&42u64.to_le_bytes());
+/// assert_eq!(hash.to_hex(), digest(message));
+/// ```
+#[derive(Clone)]
+pub struct Sha256Midstate {
+    sha: Sha256,
+    prefix_len: u64,
+}
+
+impl Sha256Midstate {
+    pub fn from_prefix<T: AsRef<[u8]>>(prefix: T) -> Self {
+        let prefix = prefix.as_ref();
+        Sha256Midstate {
+            sha: Sha256::new_with_prefix(prefix),
+            prefix_len: prefix.len() as u64,
+        }
+    }
+
+    /// The digest of the prefix followed by `suffix`.
+    pub fn finalize_with_suffix<T: AsRef<[u8]>>(&self, suffix: T) -> Sha256Hash {
+        let mut sha = self.sha.clone();
+        sha.update(suffix);
+        sha.finalize().into()
+    }
+
+    pub fn prefix_len(&self) -> u64 {
+        self.prefix_len
+    }
+}
+
+impl fmt::Debug for Sha256Midstate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sha256Midstate")
+            .field("prefix_len", &self.prefix_len)
+            .finish_non_exhaustive()
+    }
+}
+
+/// BIP-340 tagged hash, `sha256(sha256(tag) || sha256(tag) || message)`
+///
+/// The midstate after the two tag hashes, exactly one block, is cached per thread, so hashing
+/// with the same tag again only costs the message.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{tagged_digest, tagged_midstate};
+/// let hash = tagged_digest("BIP0340/challenge", b"message");
+/// assert_eq!(hash, tagged_midstate("BIP0340/challenge").finalize_with_suffix(b"message"));
+/// ```
+pub fn tagged_digest<T: AsRef<[u8]>, M: AsRef<[u8]>>(tag: T, message: M) -> Sha256Hash {
+    let tag = tag.as_ref();
+    TAG_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if !cache.contains_key(tag) {
+            if cache.len() >= TAG_CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(tag.to_vec(), tagged_midstate(tag));
+        }
+        cache[tag].finalize_with_suffix(message)
+    })
+}
+
+/// The midstate of BIP-340 tagged hashes with `tag`, to keep around instead of relying on the
+/// cache of [`tagged_digest`]
+pub fn tagged_midstate<T: AsRef<[u8]>>(tag: T) -> Sha256Midstate {
+    let tag_hash = Sha256::digest(tag);
+    Sha256Midstate::from_prefix([tag_hash, tag_hash].concat())
+}
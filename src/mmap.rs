@@ -0,0 +1,51 @@
+use crate::{calc_file, lower_hex, open_file, Error, Operation};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Files smaller than this are read rather than mapped, mapping them costs more than it saves.
+const MMAP_THRESHOLD: u64 = 1 << 20;
+
+/// Slice of the mapping handed to the hasher at a time.
+const MMAP_SLICE: usize = 1 << 20;
+
+/// sha256 digest file through a memory mapping
+///
+/// Large regular files are mapped and hashed straight from the page cache, without copying
+/// them into a buffer. Small files, non-regular files (pipes, devices) and files that can't be
+/// mapped go through the usual read loop.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated, by this or any other process, while it's being
+/// hashed. A write through the mapping's pages changes data Rust assumes immutable, and
+/// accessing a mapped page past the new end of a truncated file kills the process with `SIGBUS`
+/// on unix. Use [`try_digest`](crate::try_digest) for files that can't be guaranteed stable.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_digest_mmap;
+/// // foo.file is part of the repository and nothing writes to it.
+/// let val = unsafe { try_digest_mmap("./foo.file") }.unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// ```
+pub unsafe fn try_digest_mmap<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let f = open_file(path)?;
+    let meta = f
+        .metadata()
+        .map_err(|e| Error::io(Operation::Metadata, path, e))?;
+    if meta.is_file() && meta.len() >= MMAP_THRESHOLD {
+        // Safety: the mapping is only read, and the caller guarantees the file stays unchanged.
+        if let Ok(map) = unsafe { memmap2::Mmap::map(&f) } {
+            #[cfg(unix)]
+            let _ = map.advise(memmap2::Advice::Sequential);
+            let mut sha = Sha256::new();
+            for slice in map.chunks(MMAP_SLICE) {
+                sha.update(slice);
+            }
+            return Ok(lower_hex::encode(sha.finalize()));
+        }
+    }
+    Ok(lower_hex::encode(calc_file(path, f)?))
+}
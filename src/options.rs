@@ -0,0 +1,122 @@
+use crate::DEFAULT_BUFFER_SIZE;
+use std::fs;
+
+/// What [`try_digest_with`](crate::try_digest_with) does when the path is a symlink
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// Hash the file the link points to, like [`try_digest`](crate::try_digest).
+    #[default]
+    Follow,
+    /// Hash the target path stored in the link, without following it.
+    HashTarget,
+    /// Fail with [`Error::Symlink`](crate::Error::Symlink).
+    Error,
+}
+
+/// How a single file gets hashed
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub(crate) symlinks: SymlinkPolicy,
+    pub(crate) max_bytes: Option<u64>,
+    pub(crate) buffer_size: Option<usize>,
+    pub(crate) drop_page_cache: bool,
+    pub(crate) direct_io: bool,
+    pub(crate) aligned_buffer: bool,
+    pub(crate) rate_limit: Option<u64>,
+    pub(crate) pipelined: bool,
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What to do when the path is a symlink, [`SymlinkPolicy::Follow`] by default.
+    pub fn symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Fail with [`Error::TooLarge`](crate::Error::TooLarge) instead of hashing more than
+    /// `max_bytes` bytes. The limit is checked while reading, so it also holds for devices and
+    /// files that keep growing.
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// How many bytes are read at a time. By default it's picked from the file: small files
+    /// are read whole, files from 16 MiB on 1 MiB at a time, pipes, sockets and devices 16 KiB
+    /// at a time, and everything else [`DEFAULT_BUFFER_SIZE`] bytes at a time.
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// The buffer size set, or else the one picked for the file of `meta`.
+    pub(crate) fn buffer_size_for(&self, meta: &fs::Metadata) -> usize {
+        self.buffer_size
+            .unwrap_or_else(|| adaptive_buffer_size(meta.is_file(), meta.len()))
+    }
+
+    /// Evict the file from the page cache once hashed, so that hashing a large batch of files
+    /// doesn't push out the cache of other programs. Only has an effect on Linux, Android and
+    /// FreeBSD.
+    pub fn drop_page_cache(mut self, drop_page_cache: bool) -> Self {
+        self.drop_page_cache = drop_page_cache;
+        self
+    }
+
+    /// Read the file with direct IO, bypassing the page cache entirely, for huge files that
+    /// would otherwise evict everything else from it. Reads go through a buffer aligned to
+    /// 4 KiB, `buffer_size` is rounded up to a multiple of that. Uses `O_DIRECT` on Linux,
+    /// Android and FreeBSD and `F_NOCACHE` on macOS; where neither is available, or the file
+    /// system refuses it (e.g. tmpfs), the file is read normally. Not applied by the async
+    /// functions.
+    pub fn direct_io(mut self, direct_io: bool) -> Self {
+        self.direct_io = direct_io;
+        self
+    }
+
+    /// Allocate the read buffer aligned to 4 KiB pages, and from 2 MiB on aligned to 2 MiB with
+    /// transparent huge pages requested on Linux and Android, for sustained multi-GB/s hashing.
+    /// `buffer_size` is rounded up to a multiple of the alignment. Not applied by the async
+    /// functions.
+    pub fn aligned_buffer(mut self, aligned_buffer: bool) -> Self {
+        self.aligned_buffer = aligned_buffer;
+        self
+    }
+
+    /// Read at most `bytes_per_sec` bytes per second on average, sleeping between reads, so that
+    /// background integrity scans don't saturate the disk. Not applied by the async functions.
+    pub fn rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.rate_limit = Some(bytes_per_sec);
+        self
+    }
+
+    /// Hash on a second thread while the next `buffer_size` bytes are read, for large files on
+    /// storage where reading takes about as long as hashing. Each call starts a thread, so leave
+    /// it off when hashing many files from a thread pool. Not applied by the async functions.
+    pub fn pipelined(mut self, pipelined: bool) -> Self {
+        self.pipelined = pipelined;
+        self
+    }
+}
+
+/// Files from this size on are read [`LARGE_BUFFER_SIZE`] bytes at a time.
+const LARGE_FILE: u64 = 16 << 20;
+
+const LARGE_BUFFER_SIZE: usize = 1 << 20;
+
+/// Buffer size for pipes, sockets and devices, which rarely have more than this ready at once.
+const STREAM_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Buffer size for a regular file of `len` bytes, or for anything else when not `is_file`.
+pub(crate) fn adaptive_buffer_size(is_file: bool, len: u64) -> usize {
+    match len {
+        _ if !is_file => STREAM_BUFFER_SIZE,
+        len if len < DEFAULT_BUFFER_SIZE as u64 => (len as usize).max(1),
+        len if len >= LARGE_FILE => LARGE_BUFFER_SIZE,
+        _ => DEFAULT_BUFFER_SIZE,
+    }
+}
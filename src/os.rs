@@ -0,0 +1,70 @@
+use std::fs;
+use std::io;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+/// `FILE_FLAG_SEQUENTIAL_SCAN` from `winbase.h`.
+#[cfg(windows)]
+const FILE_FLAG_SEQUENTIAL_SCAN: u32 = 0x0800_0000;
+
+/// Open a file that is going to be read from start to end. On Windows this sets
+/// `FILE_FLAG_SEQUENTIAL_SCAN`, which makes the cache manager read ahead much further, the
+/// difference is largest on network shares.
+pub(crate) fn open_sequential(path: &Path) -> io::Result<fs::File> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(FILE_FLAG_SEQUENTIAL_SCAN)
+            .open(path)
+    }
+    #[cfg(not(windows))]
+    fs::File::open(path)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn fadvise<F: AsRawFd>(f: &F, advice: libc::c_int) {
+    // Only a hint, failures (e.g. on pipes) don't matter.
+    unsafe {
+        libc::posix_fadvise(f.as_raw_fd(), 0, 0, advice);
+    }
+}
+
+/// Tell the kernel the file is about to be read sequentially, so it reads ahead more.
+#[cfg(unix)]
+pub(crate) fn advise_sequential<F: AsRawFd>(f: &F) {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fadvise(f, libc::POSIX_FADV_SEQUENTIAL);
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    let _ = f;
+}
+
+#[cfg(not(unix))]
+pub(crate) fn advise_sequential<F>(_: &F) {}
+
+/// Drop the cached pages of a file that has been read and won't be again.
+#[cfg(unix)]
+pub(crate) fn drop_page_cache<F: AsRawFd>(f: &F) {
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fadvise(f, libc::POSIX_FADV_DONTNEED);
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+    let _ = f;
+}
+
+#[cfg(not(unix))]
+pub(crate) fn drop_page_cache<F>(_: &F) {}
+
+/// Ask for the buffer to be backed by transparent huge pages, it must be page aligned.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn advise_hugepage(buf: &mut [u8]) {
+    // Only a hint, failures (e.g. THP disabled) don't matter.
+    unsafe {
+        libc::madvise(buf.as_mut_ptr().cast(), buf.len(), libc::MADV_HUGEPAGE);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn advise_hugepage(_: &mut [u8]) {}
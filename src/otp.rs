@@ -0,0 +1,128 @@
+//! HOTP ([RFC 4226](https://www.rfc-editor.org/rfc/rfc4226)) and TOTP
+//! ([RFC 6238](https://www.rfc-editor.org/rfc/rfc6238)) one-time passwords with HMAC-SHA256
+//!
+//! ```rust
+//! use sha256::otp::Totp;
+//! use std::time::SystemTime;
+//! let totp = Totp::new();
+//! let code = totp.generate(b"12345678901234567890123456789012", SystemTime::now());
+//! assert!(totp.verify(b"12345678901234567890123456789012", &code, SystemTime::now()));
+//! ```
+
+use crate::hash::ct_eq;
+use crate::hmac_raw;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The `digits` digit HOTP code of `secret` for `counter`
+///
+/// # Panics
+///
+/// If `digits` isn't between 1 and 9.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::otp::hotp;
+/// let code = hotp(b"12345678901234567890123456789012", 0, 6);
+/// assert_eq!(code.len(), 6);
+/// ```
+pub fn hotp<S: AsRef<[u8]>>(secret: S, counter: u64, digits: u32) -> String {
+    assert!((1..=9).contains(&digits), "digits must be between 1 and 9");
+    let mac = hmac_raw(secret, counter.to_be_bytes());
+    // Dynamic truncation.
+    let offset = (mac[31] & 0xf) as usize;
+    let bin = u32::from_be_bytes([
+        mac[offset],
+        mac[offset + 1],
+        mac[offset + 2],
+        mac[offset + 3],
+    ]) & 0x7fff_ffff;
+    format!(
+        "{:0width$}",
+        bin % 10u32.pow(digits),
+        width = digits as usize
+    )
+}
+
+/// Check a HOTP `code` against the counters from `counter` to `counter + window`
+///
+/// Returns the counter that matched, the next expected counter being one past it.
+pub fn hotp_verify<S: AsRef<[u8]>>(
+    secret: S,
+    counter: u64,
+    code: &str,
+    digits: u32,
+    window: u64,
+) -> Option<u64> {
+    let secret = secret.as_ref();
+    (counter..=counter.saturating_add(window))
+        .find(|&counter| ct_eq(hotp(secret, counter, digits).as_bytes(), code.as_bytes()))
+}
+
+/// TOTP settings
+///
+/// By default codes have 6 digits, change every 30 seconds, and are accepted one step before
+/// and after the current one to allow for clock drift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Totp {
+    step: u64,
+    digits: u32,
+    window: u64,
+}
+
+impl Default for Totp {
+    fn default() -> Self {
+        Totp {
+            step: 30,
+            digits: 6,
+            window: 1,
+        }
+    }
+}
+
+impl Totp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seconds each code is valid for, 30 by default.
+    pub fn step(mut self, step: u64) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Number of digits of the codes, 6 by default.
+    ///
+    /// # Panics
+    ///
+    /// If `digits` isn't between 1 and 9.
+    pub fn digits(mut self, digits: u32) -> Self {
+        assert!((1..=9).contains(&digits), "digits must be between 1 and 9");
+        self.digits = digits;
+        self
+    }
+
+    /// Number of steps before and after the current one still accepted, 1 by default.
+    pub fn window(mut self, window: u64) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// The code of `secret` at `time`.
+    pub fn generate<S: AsRef<[u8]>>(&self, secret: S, time: SystemTime) -> String {
+        hotp(secret, self.counter(time), self.digits)
+    }
+
+    /// Whether `code` is the code of `secret` at `time`, give or take the window.
+    pub fn verify<S: AsRef<[u8]>>(&self, secret: S, code: &str, time: SystemTime) -> bool {
+        let counter = self.counter(time);
+        let first = counter.saturating_sub(self.window);
+        let window = counter - first + self.window;
+        hotp_verify(secret, first, code, self.digits, window).is_some()
+    }
+
+    fn counter(&self, time: SystemTime) -> u64 {
+        let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        secs / self.step
+    }
+}
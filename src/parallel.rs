@@ -0,0 +1,126 @@
+use crate::{calc_path, file_len, lower_hex, open_file, try_digest, Error, Operation};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// sha256 digest many files on a rayon thread pool
+///
+/// `num_threads` is the size of the pool, `0` uses rayon's global pool. A pool is built the first
+/// time a thread count is asked for and kept for the next calls with the same count, so calling
+/// this in a loop doesn't start new threads each time. Results are returned in the same order as
+/// `paths`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::par_digest_files;
+/// use std::path::PathBuf;
+/// let res = par_digest_files(vec![PathBuf::from("./foo.file")], 2);
+/// assert_eq!(res[0].1.as_ref().unwrap(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// ```
+pub fn par_digest_files<I: IntoIterator<Item = PathBuf>>(
+    paths: I,
+    num_threads: usize,
+) -> Vec<(PathBuf, Result<String, Error>)> {
+    let paths = paths.into_iter().collect::<Vec<_>>();
+    let run = move || {
+        paths
+            .into_par_iter()
+            .map(|path| {
+                let res = try_digest(&path);
+                (path, res)
+            })
+            .collect()
+    };
+    match pool(num_threads) {
+        Some(pool) => pool.install(run),
+        None => run(),
+    }
+}
+
+/// The pool of `num_threads` threads, `None` for the global pool: asked for by `0`, or if the
+/// pool couldn't be built.
+pub(crate) fn pool(num_threads: usize) -> Option<Arc<rayon::ThreadPool>> {
+    static POOLS: OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> = OnceLock::new();
+    if num_threads == 0 {
+        return None;
+    }
+    let mut pools = POOLS.get_or_init(Default::default).lock().unwrap();
+    if let Some(pool) = pools.get(&num_threads) {
+        return Some(pool.clone());
+    }
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .ok()?;
+    Some(pools.entry(num_threads).or_insert(Arc::new(pool)).clone())
+}
+
+/// "Hash of hashes" of a file split into `chunk_size` byte chunks, hashed on the rayon pool
+///
+/// This is a distinct format, *not* the sha256 of the file: the file is cut into chunks of
+/// `chunk_size` bytes (the last one may be shorter, an empty file is one empty chunk), each
+/// chunk is hashed on its own, and the result is the sha256 of the concatenated raw chunk
+/// digests. The same file only gives the same value for the same `chunk_size`. Unlike a plain
+/// digest the chunks are hashed in parallel, so this scales with the number of cores on fast
+/// storage.
+///
+/// # Panics
+///
+/// If `chunk_size` is 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, par_digest_chunked};
+/// use sha2::{Digest, Sha256};
+/// let bytes = std::fs::read("./foo.file").unwrap();
+/// let mut chunks = Sha256::new();
+/// for chunk in bytes.chunks(5) {
+///     chunks.update(Sha256::digest(chunk));
+/// }
+/// let val = par_digest_chunked("./foo.file", 5).unwrap();
+/// assert_eq!(val, hex::encode(chunks.finalize()));
+/// ```
+pub fn par_digest_chunked<P: AsRef<Path>>(path: P, chunk_size: u64) -> Result<String, Error> {
+    assert!(chunk_size > 0, "chunk_size must not be 0");
+    let path = path.as_ref();
+    let len = file_len(&open_file(path)?, path)?;
+    let chunks = len.div_ceil(chunk_size).max(1);
+    let hashes = (0..chunks)
+        .into_par_iter()
+        .map(|i| {
+            let offset = i * chunk_size;
+            let mut f = open_file(path)?;
+            f.seek(SeekFrom::Start(offset))
+                .map_err(|e| Error::io(Operation::Seek, path, e))?;
+            calc_path(path, offset, f.take(chunk_size), Sha256::new())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut sha = Sha256::new();
+    for hash in hashes {
+        sha.update(hash);
+    }
+    Ok(lower_hex::encode(sha.finalize()))
+}
+
+/// sha256 digest many in-memory inputs on the rayon global pool
+///
+/// Results are returned in the same order as `inputs`.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, par_digest_all};
+/// let val = par_digest_all(&["hello", "world"]);
+/// assert_eq!(val, vec![digest("hello"), digest("world")]);
+/// ```
+pub fn par_digest_all<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<String> {
+    inputs
+        .par_iter()
+        .map(|input| crate::__digest__(input.as_ref()))
+        .collect()
+}
@@ -0,0 +1,77 @@
+use crate::hash::ct_eq;
+use crate::HmacSha256;
+
+/// PBKDF2-HMAC-SHA256 of `password` and `salt`, `out_len` bytes long
+///
+/// The HMAC key schedule of `password` is computed once and reused for every iteration.
+///
+/// # Panics
+///
+/// If `iterations` is 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::pbkdf2;
+/// let key = pbkdf2("password", "salt", 1, 32);
+/// assert_eq!(hex::encode(key),"120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b");
+/// ```
+pub fn pbkdf2<P: AsRef<[u8]>, S: AsRef<[u8]>>(
+    password: P,
+    salt: S,
+    iterations: u32,
+    out_len: usize,
+) -> Vec<u8> {
+    assert!(iterations > 0, "iterations must not be 0");
+    let mac = HmacSha256::new(password);
+    let mut out = Vec::with_capacity(out_len);
+    let mut index = 1u32;
+    while out.len() < out_len {
+        let mut u = {
+            let mut mac = mac.clone();
+            mac.update(salt.as_ref());
+            mac.update(index.to_be_bytes());
+            mac.finalize()
+        };
+        let mut block = u;
+        for _ in 1..iterations {
+            let mut mac = mac.clone();
+            mac.update(u);
+            u = mac.finalize();
+            block.iter_mut().zip(&u).for_each(|(b, u)| *b ^= u);
+        }
+        let n = (out_len - out.len()).min(32);
+        out.extend_from_slice(&block[..n]);
+        index += 1;
+    }
+    out
+}
+
+/// Shortest stored key [`pbkdf2_verify`] accepts.
+pub const PBKDF2_MIN_KEY_LEN: usize = 16;
+
+/// Check `password` against a stored PBKDF2-HMAC-SHA256 key, comparing in constant time
+///
+/// Keys shorter than [`PBKDF2_MIN_KEY_LEN`] bytes, e.g. an empty or truncated stored value,
+/// never verify.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{pbkdf2, pbkdf2_verify};
+/// let stored = pbkdf2("password", "salt", 1000, 32);
+/// assert!(pbkdf2_verify("password", "salt", 1000, &stored));
+/// assert!(!pbkdf2_verify("hunter2", "salt", 1000, &stored));
+/// ```
+pub fn pbkdf2_verify<P: AsRef<[u8]>, S: AsRef<[u8]>>(
+    password: P,
+    salt: S,
+    iterations: u32,
+    expected: &[u8],
+) -> bool {
+    expected.len() >= PBKDF2_MIN_KEY_LEN
+        && ct_eq(
+            &pbkdf2(password, salt, iterations, expected.len()),
+            expected,
+        )
+}
@@ -0,0 +1,83 @@
+use crate::{CalculatorInput, CalculatorSelector};
+use std::io;
+use std::ops::DerefMut;
+
+/// Like `calc_raw_buf`, but hashing each chunk on another thread while the next one is read
+/// into the other of `bufs`, passing the two buffers back and forth between the threads.
+///
+/// Reading stays on the calling thread, so `input` needn't be `Send`. Whichever side panics,
+/// the other one sees its channels disconnect and stops, and the panic is propagated.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn calc_pipelined<I, S, B>(
+    mut input: I,
+    selector: S,
+    bufs: [B; 2],
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector + Send,
+    S::FinishType: Send,
+    B: DerefMut<Target = [u8]> + Send,
+{
+    use std::sync::mpsc;
+    use std::thread;
+
+    let (full_tx, full_rx) = mpsc::sync_channel::<(B, usize)>(1);
+    let (empty_tx, empty_rx) = mpsc::channel::<B>();
+    for buf in bufs {
+        empty_tx.send(buf).unwrap();
+    }
+    // Everything is moved in, so that an unwinding reader drops its channel ends before the
+    // scope waits for the hashing thread.
+    thread::scope(move |s| {
+        let hasher = s.spawn(move || {
+            let mut selector = selector;
+            for (buf, n) in full_rx {
+                selector.update_inner(&buf[..n]);
+                // The reader may already be done, then the buffer isn't needed anymore.
+                let _ = empty_tx.send(buf);
+            }
+            selector.finish_inner()
+        });
+        let mut offset = 0;
+        let res = loop {
+            // Only fails when the hashing thread panicked, which `join` reports below.
+            let mut buf = match empty_rx.recv() {
+                Ok(buf) => buf,
+                Err(_) => break Ok(()),
+            };
+            match input.read_inner(&mut buf) {
+                Ok(0) => break Ok(()),
+                Ok(n) => {
+                    offset += n as u64;
+                    if full_tx.send((buf, n)).is_err() {
+                        break Ok(());
+                    }
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        drop(full_tx);
+        let hash = hasher
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+        res.map(|()| (hash, offset)).map_err(|e| (e, offset))
+    })
+}
+
+/// Without threads there is nothing to overlap, this is `calc_raw_buf`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn calc_pipelined<I, S, B>(
+    input: I,
+    selector: S,
+    bufs: [B; 2],
+) -> Result<(S::FinishType, u64), (io::Error, u64)>
+where
+    I: CalculatorInput,
+    S: CalculatorSelector + Send,
+    S::FinishType: Send,
+    B: DerefMut<Target = [u8]> + Send,
+{
+    let [mut buf, _] = bufs;
+    crate::calc_raw_buf(input, selector, &mut buf)
+}
@@ -0,0 +1,51 @@
+//! SHA-256 building blocks of RSA padding schemes ([RFC 8017](https://www.rfc-editor.org/rfc/rfc8017))
+//!
+//! ```rust
+//! use sha256::{digest, pkcs1, Sha256Hash};
+//! let mask = pkcs1::mgf1(b"seed", 100);
+//! assert_eq!(mask.len(), 100);
+//! let hash: Sha256Hash = digest("hello").parse().unwrap();
+//! let info = pkcs1::digest_info(&hash);
+//! assert_eq!(info[..19], pkcs1::DIGEST_INFO_PREFIX);
+//! ```
+
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+
+/// DER encoding of the `DigestInfo` of a SHA-256 hash, up to the hash itself
+///
+/// As used by EMSA-PKCS1-v1_5 signatures: `SEQUENCE { SEQUENCE { OID 2.16.840.1.101.3.4.2.1,
+/// NULL }, OCTET STRING (32 bytes) }`.
+pub const DIGEST_INFO_PREFIX: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The DER `DigestInfo` of `hash`, [`DIGEST_INFO_PREFIX`] followed by the hash
+pub fn digest_info(hash: &Sha256Hash) -> [u8; 51] {
+    let mut info = [0; 51];
+    info[..19].copy_from_slice(&DIGEST_INFO_PREFIX);
+    info[19..].copy_from_slice(hash.as_bytes());
+    info
+}
+
+/// MGF1 with SHA-256, `len` bytes of mask generated from `seed`, as used by RSA-OAEP and RSA-PSS
+///
+/// # Panics
+///
+/// If `len` is over 2<sup>32</sup> blocks of 32 bytes.
+pub fn mgf1<T: AsRef<[u8]>>(seed: T, len: usize) -> Vec<u8> {
+    let blocks = len.div_ceil(32);
+    assert!(blocks as u64 <= 1 << 32, "MGF1 mask too long");
+    let sha = Sha256::new_with_prefix(seed);
+    let mut mask = Vec::with_capacity(blocks * 32);
+    for counter in 0..blocks as u64 {
+        mask.extend_from_slice(
+            &sha.clone()
+                .chain_update((counter as u32).to_be_bytes())
+                .finalize(),
+        );
+    }
+    mask.truncate(len);
+    mask
+}
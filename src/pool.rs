@@ -0,0 +1,55 @@
+use crate::Sha256Hash;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+
+thread_local! {
+    static HASHER: RefCell<Sha256> = RefCell::new(Sha256::new());
+}
+
+/// Run `f` with this thread's reusable hasher
+///
+/// The hasher is reset when `f` gets it, so state left behind by an earlier call, even one that
+/// panicked, doesn't leak into it. Nested calls get a hasher of their own.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, with_hasher};
+/// use sha2::Digest;
+/// let hash = with_hasher(|sha| {
+///     sha.update("hel");
+///     sha.update("lo");
+///     sha.finalize_reset()
+/// });
+/// assert_eq!(hex::encode(hash), digest("hello"));
+/// ```
+pub fn with_hasher<R, F: FnOnce(&mut Sha256) -> R>(f: F) -> R {
+    HASHER.with(|hasher| match hasher.try_borrow_mut() {
+        Ok(mut sha) => {
+            Digest::reset(&mut *sha);
+            f(&mut sha)
+        }
+        Err(_) => f(&mut Sha256::new()),
+    })
+}
+
+/// sha256 digest `input` into `out`, replacing its content and reusing its allocation
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, digest_into};
+/// let mut out = String::new();
+/// for input in ["hello", "world"] {
+///     digest_into(input, &mut out);
+///     assert_eq!(out, digest(input));
+/// }
+/// ```
+pub fn digest_into<T: AsRef<[u8]>>(input: T, out: &mut String) {
+    let hash = with_hasher(|sha| {
+        sha.update(input);
+        sha.finalize_reset()
+    });
+    out.clear();
+    out.push_str(&Sha256Hash::from(hash).to_hex_digest());
+}
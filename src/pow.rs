@@ -0,0 +1,192 @@
+//! Hashcash-style proof of work
+//!
+//! A nonce proves work for `data` at `difficulty_bits` when `sha256(data || nonce)`, the nonce
+//! as 8 little-endian bytes, starts with at least that many zero bits.
+//!
+//! ```rust
+//! use sha256::pow;
+//! let (nonce, hash) = pow::mine(b"hello", 12, 0..u64::MAX).unwrap();
+//! assert!(pow::leading_zero_bits(&hash) >= 12);
+//! assert!(pow::check(b"hello", nonce, 12));
+//! ```
+
+use crate::{Sha256Hash, Sha256Midstate};
+use std::ops::Range;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Nonces a [`Miner`] thread takes at once before checking whether to stop.
+#[cfg(not(target_arch = "wasm32"))]
+const BATCH: u64 = 1 << 14;
+
+/// Search `range` in order for the first nonce proving `difficulty_bits` of work for `prefix`.
+///
+/// The prefix is hashed once, each try only costs the final block or two. Returns `None` if no
+/// nonce in the range is good enough.
+///
+/// # Panics
+///
+/// If `difficulty_bits` is over 256.
+pub fn mine<T: AsRef<[u8]>>(
+    prefix: T,
+    difficulty_bits: u32,
+    range: Range<u64>,
+) -> Option<(u64, Sha256Hash)> {
+    assert!(
+        difficulty_bits <= 256,
+        "difficulty_bits must be at most 256"
+    );
+    let midstate = Sha256Midstate::from_prefix(prefix);
+    mine_from(&midstate, difficulty_bits, range)
+}
+
+pub(crate) fn mine_from(
+    midstate: &Sha256Midstate,
+    difficulty_bits: u32,
+    range: Range<u64>,
+) -> Option<(u64, Sha256Hash)> {
+    range
+        .map(|nonce| (nonce, midstate.finalize_with_suffix(nonce.to_le_bytes())))
+        .find(|(_, hash)| leading_zero_bits(hash) >= difficulty_bits)
+}
+
+/// Whether `nonce` proves `difficulty_bits` of work for `data`.
+pub fn check<T: AsRef<[u8]>>(data: T, nonce: u64, difficulty_bits: u32) -> bool {
+    let hash = Sha256Midstate::from_prefix(data).finalize_with_suffix(nonce.to_le_bytes());
+    leading_zero_bits(&hash) >= difficulty_bits
+}
+
+/// The number of zero bits `hash` starts with, from 0 to 256, see
+/// [`Sha256Hash::leading_zero_bits`].
+pub fn leading_zero_bits(hash: &Sha256Hash) -> u32 {
+    hash.leading_zero_bits()
+}
+
+/// Searches for proof-of-work nonces on several threads
+///
+/// The threads take batches of nonces from the range in turn, and all stop shortly after one
+/// of them finds a solution or the cancel flag is set. The solution found is not necessarily
+/// the lowest good nonce in the range, unlike with [`mine`].
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::pow::{self, Miner};
+/// let report = Miner::new().threads(2).mine(b"hello", 12, 0..u64::MAX);
+/// let (nonce, _) = report.solution.unwrap();
+/// assert!(pow::check(b"hello", nonce, 12));
+/// println!("{:.0} H/s", report.hashes_per_sec());
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+pub struct Miner {
+    threads: usize,
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Miner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of threads to search on, `0` (the default) for one per core.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Stop searching, without a solution, once `cancel` is set to `true`.
+    pub fn cancel_flag(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Search `range` for a nonce proving `difficulty_bits` of work for `prefix`.
+    ///
+    /// # Panics
+    ///
+    /// If `difficulty_bits` is over 256.
+    pub fn mine<T: AsRef<[u8]>>(
+        &self,
+        prefix: T,
+        difficulty_bits: u32,
+        range: Range<u64>,
+    ) -> MineReport {
+        assert!(
+            difficulty_bits <= 256,
+            "difficulty_bits must be at most 256"
+        );
+        let threads = match self.threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let midstate = Sha256Midstate::from_prefix(prefix);
+        let next_batch = AtomicU64::new(0);
+        let found = AtomicBool::new(false);
+        let solution = Mutex::new(None);
+        let hashes = AtomicU64::new(0);
+        let stopped = || {
+            found.load(Ordering::Relaxed)
+                || self
+                    .cancel
+                    .as_ref()
+                    .is_some_and(|c| c.load(Ordering::Relaxed))
+        };
+        let start = Instant::now();
+        std::thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    while !stopped() {
+                        let batch = next_batch.fetch_add(1, Ordering::Relaxed);
+                        let from = match batch
+                            .checked_mul(BATCH)
+                            .and_then(|offset| range.start.checked_add(offset))
+                        {
+                            Some(from) if from < range.end => from,
+                            _ => break,
+                        };
+                        let to = from.saturating_add(BATCH).min(range.end);
+                        match mine_from(&midstate, difficulty_bits, from..to) {
+                            Some((nonce, hash)) => {
+                                hashes.fetch_add(nonce - from + 1, Ordering::Relaxed);
+                                if !found.swap(true, Ordering::Relaxed) {
+                                    *solution.lock().unwrap() = Some((nonce, hash));
+                                }
+                            }
+                            None => {
+                                hashes.fetch_add(to - from, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        MineReport {
+            solution: solution.into_inner().unwrap(),
+            hashes: hashes.into_inner(),
+            elapsed: start.elapsed(),
+        }
+    }
+}
+
+/// The outcome of a [`Miner`] search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MineReport {
+    /// The nonce found and its hash, `None` if the range was exhausted or the search cancelled.
+    pub solution: Option<(u64, Sha256Hash)>,
+    /// Number of nonces tried by all threads together.
+    pub hashes: u64,
+    pub elapsed: Duration,
+}
+
+impl MineReport {
+    pub fn hashes_per_sec(&self) -> f64 {
+        self.hashes as f64 / self.elapsed.as_secs_f64()
+    }
+}
@@ -0,0 +1,11 @@
+use crate::Sha256Hash;
+use std::time::Duration;
+
+/// The digest of a file with some accounting, see [`try_digest_ext`](crate::try_digest_ext)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestReport {
+    pub hash: Sha256Hash,
+    pub bytes_read: u64,
+    /// Time spent opening, reading and hashing the file.
+    pub elapsed: Duration,
+}
@@ -0,0 +1,36 @@
+use crate::lower_hex;
+use sha2::{Digest, Sha256};
+
+/// sha256 digest the body of `url`, returning the digest and the body length
+///
+/// The body is hashed chunk by chunk as it arrives, it is never fully buffered. Non-success
+/// status codes are returned as errors. No TLS backend is enabled by this crate, enable one of
+/// reqwest's TLS features in your own `Cargo.toml` to hash `https` urls.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sha256::digest_url;
+/// tokio_test::block_on(async{
+/// let (val, len) = digest_url("http://example.com/foo.file").await.unwrap();
+/// println!("{val} {len}");
+/// });
+/// ```
+pub async fn digest_url<U: reqwest::IntoUrl>(url: U) -> Result<(String, u64), reqwest::Error> {
+    let response = reqwest::get(url).await?;
+    digest_response(response).await
+}
+
+/// sha256 digest an already sent `reqwest::Response`, returning the digest and the body length
+///
+/// Use this one to hash responses from a custom `reqwest::Client`.
+pub async fn digest_response(response: reqwest::Response) -> Result<(String, u64), reqwest::Error> {
+    let mut response = response.error_for_status()?;
+    let mut sha = Sha256::new();
+    let mut len = 0;
+    while let Some(chunk) = response.chunk().await? {
+        len += chunk.len() as u64;
+        sha.update(&chunk);
+    }
+    Ok((lower_hex::encode(sha.finalize()), len))
+}
@@ -0,0 +1,152 @@
+use crate::{FieldHasher, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+/// A consistent-hashing ring mapping keys to nodes
+///
+/// Each node is placed at `vnodes` points of a 64-bit ring: the first 8 bytes of the
+/// [`FieldHasher`] digest of the node and the point index, a 32-bit big endian integer. A key
+/// belongs to the first node point at or after the first 8 bytes of `sha256(key)`, wrapping
+/// around. Adding or removing a node only moves the keys of that node.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::HashRing;
+/// let mut ring = HashRing::new(100);
+/// ring.add("cache-a");
+/// ring.add("cache-b");
+/// let node = *ring.get("user:42").unwrap();
+/// ring.add("cache-c");
+/// let moved = *ring.get("user:42").unwrap();
+/// assert!(moved == node || moved == "cache-c");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HashRing<N> {
+    vnodes: usize,
+    nodes: Vec<N>,
+    points: BTreeMap<u64, N>,
+}
+
+impl<N: AsRef<[u8]> + Clone + PartialEq> HashRing<N> {
+    /// An empty ring placing each node at `vnodes` points.
+    ///
+    /// # Panics
+    ///
+    /// If `vnodes` is 0.
+    pub fn new(vnodes: usize) -> Self {
+        assert!(vnodes > 0, "vnodes must not be 0");
+        HashRing {
+            vnodes,
+            nodes: Vec::new(),
+            points: BTreeMap::new(),
+        }
+    }
+
+    /// Add `node`, unless it's already on the ring.
+    pub fn add(&mut self, node: N) {
+        if self.nodes.contains(&node) {
+            return;
+        }
+        for point in self.points_of(&node) {
+            self.points.entry(point).or_insert_with(|| node.clone());
+        }
+        self.nodes.push(node);
+    }
+
+    /// Remove `node`, returning whether it was on the ring.
+    pub fn remove(&mut self, node: &N) -> bool {
+        let index = match self.nodes.iter().position(|n| n == node) {
+            Some(index) => index,
+            None => return false,
+        };
+        self.nodes.remove(index);
+        for point in self.points_of(node) {
+            if self.points.get(&point) == Some(node) {
+                self.points.remove(&point);
+            }
+        }
+        true
+    }
+
+    /// The node `key` belongs to, `None` if the ring is empty.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&N> {
+        let point = ring_point(&Sha256::digest(key));
+        self.points
+            .range(point..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, node)| node)
+    }
+
+    /// The nodes on the ring, in the order they were added.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn points_of<'a>(&self, node: &'a N) -> impl Iterator<Item = u64> + 'a {
+        (0..self.vnodes as u32).map(move |i| {
+            let hash = FieldHasher::new()
+                .field(node)
+                .field(i.to_be_bytes())
+                .finish();
+            ring_point(hash.as_bytes())
+        })
+    }
+}
+
+fn ring_point(hash: &[u8]) -> u64 {
+    u64::from_be_bytes(hash[..8].try_into().unwrap())
+}
+
+/// Rendezvous (highest random weight) hashing: the node of `nodes` that `key` belongs to
+///
+/// Each node scores the [`FieldHasher`] digest of the node and the key, the highest score wins.
+/// Removing a node only moves the keys of that node, without keeping any state. Returns `None`
+/// if `nodes` is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::rendezvous_select;
+/// let nodes = ["cache-a", "cache-b", "cache-c"];
+/// let node = rendezvous_select("user:42", &nodes).unwrap();
+/// assert!(nodes.contains(node));
+/// ```
+pub fn rendezvous_select<K, N>(key: K, nodes: &[N]) -> Option<&N>
+where
+    K: AsRef<[u8]>,
+    N: AsRef<[u8]>,
+{
+    let key = key.as_ref();
+    nodes.iter().max_by_key(|node| rendezvous_score(key, node))
+}
+
+/// The `n` nodes of `nodes` with the highest rendezvous scores for `key`, best first, to pick
+/// replicas
+pub fn rendezvous_top<K, N>(key: K, nodes: &[N], n: usize) -> Vec<&N>
+where
+    K: AsRef<[u8]>,
+    N: AsRef<[u8]>,
+{
+    let key = key.as_ref();
+    let mut scored = nodes
+        .iter()
+        .map(|node| (rendezvous_score(key, node), node))
+        .collect::<Vec<_>>();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().take(n).map(|(_, node)| node).collect()
+}
+
+fn rendezvous_score<N: AsRef<[u8]>>(key: &[u8], node: &N) -> Sha256Hash {
+    FieldHasher::new().field(node).field(key).finish()
+}
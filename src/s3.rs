@@ -0,0 +1,47 @@
+use crate::{base64, calc_raw_offset, open_file, Error};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// The SHA-256 checksum S3 reports for a multipart upload of a file in `part_size` byte parts
+///
+/// That's the digest of the concatenated raw digests of the parts, as `<base64>-<parts>`. An
+/// empty file counts as one empty part.
+///
+/// # Panics
+///
+/// If `part_size` is 0.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::s3_composite_checksum;
+/// let val = s3_composite_checksum("./foo.file", 5).unwrap();
+/// assert!(val.ends_with("-3"));
+/// ```
+pub fn s3_composite_checksum<P: AsRef<Path>>(path: P, part_size: u64) -> Result<String, Error> {
+    assert!(part_size > 0, "part_size must not be 0");
+    let path = path.as_ref();
+    let mut reader = open_file(path)?;
+    let mut composite = Sha256::new();
+    let (mut offset, mut parts) = (0, 0);
+    loop {
+        let part = (&mut reader).take(part_size);
+        let (hash, len) = calc_raw_offset(part, Sha256::new())
+            .map_err(|(e, n)| Error::read(path, offset + n, e))?;
+        if len == 0 && parts > 0 {
+            break;
+        }
+        composite.update(hash);
+        parts += 1;
+        offset += len;
+        if len < part_size {
+            break;
+        }
+    }
+    Ok(format!(
+        "{}-{}",
+        base64::encode(&composite.finalize()),
+        parts
+    ))
+}
@@ -0,0 +1,44 @@
+//! Deterministic bucketing and sampling from the sha256 of an input
+//!
+//! Both only use the first 8 bytes of `sha256(input)`, read as a 64-bit big endian unsigned
+//! integer `x`, and are meant to give the same answer in any language:
+//!
+//! - [`bucket`] is `x mod n`.
+//! - [`sample`] is `(x >> 11) / 2^53 < rate`, computed in IEEE 754 double precision, where
+//!   `x >> 11` and `2^53` are exact.
+//!
+//! These definitions are stable: they won't change within a major version of this crate.
+//!
+//! ```rust
+//! use sha256::sampling;
+//! let variant = sampling::bucket("experiment-7:user-42", 2);
+//! assert!(variant < 2);
+//! let traced = sampling::sample("trace-0af7651916cd43dd", 0.1);
+//! assert_eq!(traced, sampling::sample("trace-0af7651916cd43dd", 0.1));
+//! ```
+
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+
+/// The bucket among `n` that `input` falls in, from 0 to `n - 1`
+///
+/// Prefix the input with an experiment name to get independent assignments per experiment.
+///
+/// # Panics
+///
+/// If `n` is 0.
+pub fn bucket<T: AsRef<[u8]>>(input: T, n: u32) -> u32 {
+    assert!(n > 0, "n must not be 0");
+    (prefix_u64(input) % n as u64) as u32
+}
+
+/// Whether `input` is sampled at `rate`, from 0 (never) to 1 (always)
+///
+/// Inputs sampled at a rate are also sampled at any higher rate.
+pub fn sample<T: AsRef<[u8]>>(input: T, rate: f64) -> bool {
+    ((prefix_u64(input) >> 11) as f64 / (1u64 << 53) as f64) < rate
+}
+
+fn prefix_u64<T: AsRef<[u8]>>(input: T) -> u64 {
+    u64::from_be_bytes(Sha256::digest(input)[..8].try_into().unwrap())
+}
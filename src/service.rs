@@ -0,0 +1,148 @@
+use crate::{calc_file, calc_raw_offset, open_file, Error, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::any::Any;
+use std::io::{self, Read};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Hashes files and readers on a pool of threads of its own
+///
+/// Jobs are submitted through [`HashHandle`]s, which can be cloned and sent to other threads,
+/// and each gives back a [`PendingDigest`] to collect the result from. Dropping the service
+/// finishes the jobs already submitted, then stops the threads; jobs submitted after that fail.
+/// A job that panics, e.g. in a reader's `read`, fails with an error and its thread carries on.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::HashService;
+/// let service = HashService::new(2);
+/// let handle = service.handle();
+/// let pending = handle.submit_path("./foo.file");
+/// let hash = pending.wait().unwrap();
+/// assert_eq!(hash.to_hex(),"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// ```
+pub struct HashService {
+    jobs: Arc<Mutex<Option<Sender<Job>>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+/// Submits jobs to a [`HashService`]
+#[derive(Clone)]
+pub struct HashHandle {
+    jobs: Arc<Mutex<Option<Sender<Job>>>>,
+}
+
+/// The digest of a job submitted to a [`HashService`], once it's done
+pub struct PendingDigest {
+    result: Receiver<Result<Sha256Hash, Error>>,
+}
+
+impl HashService {
+    /// Start a service with `threads` threads, at least one.
+    pub fn new(threads: usize) -> Self {
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                let rx = rx.clone();
+                thread::spawn(move || loop {
+                    // The lock is released before running the job.
+                    let job = rx.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        HashService {
+            jobs: Arc::new(Mutex::new(Some(tx))),
+            workers,
+        }
+    }
+
+    pub fn handle(&self) -> HashHandle {
+        HashHandle {
+            jobs: self.jobs.clone(),
+        }
+    }
+}
+
+impl Drop for HashService {
+    fn drop(&mut self) {
+        self.jobs.lock().unwrap().take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl HashHandle {
+    /// Hash the file at `path`.
+    pub fn submit_path<P: Into<PathBuf>>(&self, path: P) -> PendingDigest {
+        let path = path.into();
+        self.submit(move || {
+            let f = open_file(&path)?;
+            Ok(calc_file(&path, f)?.into())
+        })
+    }
+
+    /// Hash everything `reader` yields.
+    pub fn submit_reader<R: Read + Send + 'static>(&self, reader: R) -> PendingDigest {
+        self.submit(move || {
+            let (hash, _) = calc_raw_offset(reader, Sha256::new()).map_err(|(e, _)| e)?;
+            Ok(hash.into())
+        })
+    }
+
+    fn submit<F>(&self, job: F) -> PendingDigest
+    where
+        F: FnOnce() -> Result<Sha256Hash, Error> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel();
+        if let Some(jobs) = &*self.jobs.lock().unwrap() {
+            let _ = jobs.send(Box::new(move || {
+                let res =
+                    panic::catch_unwind(AssertUnwindSafe(job)).unwrap_or_else(|p| Err(panicked(p)));
+                let _ = tx.send(res);
+            }));
+        }
+        PendingDigest { result: rx }
+    }
+}
+
+impl PendingDigest {
+    /// Block until the job is done.
+    pub fn wait(self) -> Result<Sha256Hash, Error> {
+        self.result.recv().unwrap_or_else(|_| Err(stopped()))
+    }
+
+    /// The result if the job is done, without blocking.
+    pub fn try_wait(&self) -> Option<Result<Sha256Hash, Error>> {
+        match self.result.try_recv() {
+            Ok(res) => Some(res),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(stopped())),
+        }
+    }
+}
+
+/// The job was submitted after the service was dropped.
+fn stopped() -> Error {
+    io::Error::other("the hash service stopped before the job was done").into()
+}
+
+fn panicked(payload: Box<dyn Any + Send>) -> Error {
+    let msg = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>");
+    io::Error::other(format!("the hash job panicked: {}", msg)).into()
+}
@@ -0,0 +1,69 @@
+use crate::{digest, hmac, hmac_raw};
+
+/// The AWS Signature Version 4 signing key for a day, region and service
+///
+/// `date` is `YYYYMMDD`, the date part of the request's `X-Amz-Date`. The key only depends on
+/// these four values, so it can be cached for the day.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::sigv4_signing_key;
+/// let key = sigv4_signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20120215", "us-east-1", "iam");
+/// assert_eq!(hex::encode(key),"f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d");
+/// ```
+pub fn sigv4_signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> [u8; 32] {
+    let k_date = hmac_raw(format!("AWS4{}", secret_key), date);
+    let k_region = hmac_raw(k_date, region);
+    let k_service = hmac_raw(k_region, service);
+    hmac_raw(k_service, "aws4_request")
+}
+
+/// The hex signature of a SigV4 string to sign, under a key from [`sigv4_signing_key`]
+pub fn sigv4_signature<S: AsRef<[u8]>>(signing_key: &[u8; 32], string_to_sign: S) -> String {
+    hmac(signing_key, string_to_sign)
+}
+
+/// The SigV4 string to sign of a canonical request
+///
+/// `amz_date` is the request's `X-Amz-Date`, `YYYYMMDD'T'HHMMSS'Z'`, and `canonical_request` the
+/// request in the canonical form of the SigV4 specification, which gets hashed into:
+///
+/// ```text
+/// AWS4-HMAC-SHA256\n<amz_date>\n<YYYYMMDD>/<region>/<service>/aws4_request\n<hex sha256(canonical_request)>
+/// ```
+///
+/// # Panics
+///
+/// If `amz_date` doesn't start with an 8 character date.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{sigv4_signature, sigv4_signing_key, sigv4_string_to_sign};
+/// let canonical_request = "GET\n/\nAction=ListUsers&Version=2010-05-08\n\
+///     content-type:application/x-www-form-urlencoded; charset=utf-8\nhost:iam.amazonaws.com\n\
+///     x-amz-date:20150830T123600Z\n\ncontent-type;host;x-amz-date\n\
+///     e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+/// let string_to_sign =
+///     sigv4_string_to_sign("20150830T123600Z", "us-east-1", "iam", canonical_request);
+/// let key = sigv4_signing_key("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY", "20150830", "us-east-1", "iam");
+/// assert_eq!(sigv4_signature(&key, string_to_sign),"5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7");
+/// ```
+pub fn sigv4_string_to_sign<C: AsRef<[u8]>>(
+    amz_date: &str,
+    region: &str,
+    service: &str,
+    canonical_request: C,
+) -> String {
+    let date = amz_date.get(..8);
+    assert!(date.is_some(), "amz_date must start with a YYYYMMDD date");
+    format!(
+        "AWS4-HMAC-SHA256\n{}\n{}/{}/{}/aws4_request\n{}",
+        amz_date,
+        date.unwrap(),
+        region,
+        service,
+        digest(canonical_request.as_ref())
+    )
+}
@@ -0,0 +1,126 @@
+use crate::{lower_hex, Error};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+static ZEROS: [u8; 64 * 1024] = [0; 64 * 1024];
+
+/// sha256 digest a file, without reading its holes
+///
+/// The digest is the same as [`try_digest`](crate::try_digest)'s. On Linux, Android and FreeBSD
+/// the data regions of the file are found with `SEEK_DATA`/`SEEK_HOLE` and only those are read,
+/// holes are fed to the hasher as zeros. Elsewhere, or on file systems without hole reporting,
+/// the whole file is read. A file that shrinks while it is hashed fails with
+/// [`io::ErrorKind::UnexpectedEof`] rather than having its missing end hashed as a hole.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::try_digest_sparse;
+/// let val = try_digest_sparse("./foo.file").unwrap();
+/// assert_eq!(val,"433855b7d2b96c23a6f60e70c655eb4305e8806b682a9596a200642f947259b1");
+/// ```
+pub fn try_digest_sparse<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let path = path.as_ref();
+    let mut f = crate::open_file(path)?;
+    let mut sha = Sha256::new();
+    hash_sparse(path, &mut f, &mut sha)?;
+    Ok(lower_hex::encode(sha.finalize()))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn hash_sparse(path: &Path, f: &mut fs::File, sha: &mut Sha256) -> Result<(), Error> {
+    use crate::Operation;
+    use std::io::{Seek, SeekFrom};
+    use std::os::unix::io::AsRawFd;
+
+    let seek_error = |e| Error::io(Operation::Seek, path, e);
+    let len = crate::file_len(f, path)?;
+    let mut pos = 0;
+    while pos < len {
+        let data = match seek_region(f, pos, libc::SEEK_DATA) {
+            Ok(data) => data.min(len),
+            // No data after `pos`, the rest of the file is a hole, unless it was truncated.
+            Err(e) if e.raw_os_error() == Some(libc::ENXIO) => {
+                let now = crate::file_len(f, path)?;
+                if now < len {
+                    return Err(Error::read(path, now, shrunk()));
+                }
+                len
+            }
+            // Hole reporting isn't supported, read everything left.
+            Err(_) => {
+                f.seek(SeekFrom::Start(pos)).map_err(seek_error)?;
+                return hash_read(path, f, sha, pos, None);
+            }
+        };
+        hash_zeros(sha, data - pos);
+        if data == len {
+            break;
+        }
+        let hole = seek_region(f, data, libc::SEEK_HOLE)
+            .map_err(seek_error)?
+            .min(len);
+        f.seek(SeekFrom::Start(data)).map_err(seek_error)?;
+        hash_read(path, f, sha, data, Some(hole - data))?;
+        pos = hole;
+    }
+    return Ok(());
+
+    fn seek_region(f: &fs::File, pos: u64, whence: libc::c_int) -> io::Result<u64> {
+        let res = unsafe { libc::lseek(f.as_raw_fd(), pos as libc::off_t, whence) };
+        if res < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(res as u64)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+fn hash_sparse(path: &Path, f: &mut fs::File, sha: &mut Sha256) -> Result<(), Error> {
+    hash_read(path, f, sha, 0, None)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn hash_zeros(sha: &mut Sha256, mut len: u64) {
+    while len > 0 {
+        let n = len.min(ZEROS.len() as u64) as usize;
+        sha.update(&ZEROS[..n]);
+        len -= n as u64;
+    }
+}
+
+/// Feed exactly `len` bytes of `f`, positioned at `offset`, to the hasher, or everything up to
+/// the end of the file if `len` is `None`.
+pub(crate) fn hash_read(
+    path: &Path,
+    f: &mut fs::File,
+    sha: &mut Sha256,
+    mut offset: u64,
+    len: Option<u64>,
+) -> Result<(), Error> {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut input = f.take(len.unwrap_or(u64::MAX));
+    loop {
+        let n = input
+            .read(&mut buf)
+            .map_err(|e| Error::read(path, offset, e))?;
+        if n == 0 {
+            if input.limit() > 0 && len.is_some() {
+                return Err(Error::read(path, offset, shrunk()));
+            }
+            return Ok(());
+        }
+        sha.update(&buf[..n]);
+        offset += n as u64;
+    }
+}
+
+fn shrunk() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "file shrank while being hashed",
+    )
+}
@@ -0,0 +1,102 @@
+use crate::atomic::TempFile;
+use crate::{Error, Operation, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A directory of blobs stored under their sha256
+///
+/// Blobs live at `objects/<first 2 hex digits>/<other 62 hex digits>` under the root, like git
+/// objects. They are written to a temporary file and renamed into place, so a blob is either
+/// complete or absent, and storing the same content twice keeps a single copy.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{digest, BlobStore};
+/// let store = BlobStore::open(std::env::temp_dir().join("sha256_doc_blob_store")).unwrap();
+/// let hash = store.put(&b"hello"[..]).unwrap();
+/// assert_eq!(hash.to_hex(), digest("hello"));
+/// assert!(store.contains(&hash));
+/// assert_eq!(store.get(&hash).unwrap(), b"hello");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlobStore {
+    root: PathBuf,
+    verify: bool,
+}
+
+impl BlobStore {
+    /// Use the store at `root`, creating its directory if needed.
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, Error> {
+        let root = root.as_ref().to_path_buf();
+        let objects = root.join("objects");
+        fs::create_dir_all(&objects).map_err(|e| Error::io(Operation::Write, &objects, e))?;
+        Ok(BlobStore {
+            root,
+            verify: false,
+        })
+    }
+
+    /// Check that blobs still have their digest when reading them with
+    /// [`get`](BlobStore::get), off by default.
+    pub fn verify_on_read(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Where the blob with digest `hash` is, whether it exists or not.
+    pub fn path(&self, hash: &Sha256Hash) -> PathBuf {
+        let hex = hash.to_hex();
+        self.root.join("objects").join(&hex[..2]).join(&hex[2..])
+    }
+
+    pub fn contains(&self, hash: &Sha256Hash) -> bool {
+        self.path(hash).is_file()
+    }
+
+    /// Store everything `reader` yields, returning its digest.
+    pub fn put<R: Read>(&self, reader: R) -> Result<Sha256Hash, Error> {
+        let mut tmp = TempFile::new(&self.root.join("objects").join("incoming"))?;
+        let hash = tmp.write_hashed(reader, |offset, source| Error::Io {
+            source,
+            path: None,
+            op: Some(Operation::Read),
+            offset: Some(offset),
+        })?;
+        let path = self.path(&hash);
+        if path.is_file() {
+            return Ok(hash);
+        }
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir).map_err(|e| Error::io(Operation::Write, dir, e))?;
+        tmp.persist(&path)?;
+        Ok(hash)
+    }
+
+    /// The content of the blob with digest `hash`
+    ///
+    /// A missing blob is an [`Error::Io`] of kind `NotFound`. With
+    /// [`verify_on_read`](BlobStore::verify_on_read), a blob that was corrupted is an
+    /// [`Error::Mismatch`].
+    pub fn get(&self, hash: &Sha256Hash) -> Result<Vec<u8>, Error> {
+        let path = self.path(hash);
+        let data = fs::read(&path).map_err(|e| Error::io(Operation::Read, &path, e))?;
+        if self.verify {
+            let actual: Sha256Hash = Sha256::digest(&data).into();
+            if !actual.ct_eq(hash) {
+                return Err(Error::Mismatch {
+                    path,
+                    expected: *hash,
+                    actual,
+                });
+            }
+        }
+        Ok(data)
+    }
+}
@@ -0,0 +1,56 @@
+use std::io::{self, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Keeps the average read rate under a limit, by sleeping whenever it gets ahead.
+pub(crate) struct Throttle {
+    /// The rate and when reading started, the clock isn't read at all without a limit.
+    limit: Option<(u64, Instant)>,
+    done: u64,
+}
+
+impl Throttle {
+    /// `None` doesn't limit anything.
+    pub(crate) fn new(bytes_per_sec: Option<u64>) -> Self {
+        Throttle {
+            limit: bytes_per_sec.map(|rate| (rate.max(1), Instant::now())),
+            done: 0,
+        }
+    }
+
+    fn consume(&mut self, n: usize) {
+        let (rate, start) = match self.limit {
+            Some(limit) => limit,
+            None => return,
+        };
+        self.done += n as u64;
+        let due = Duration::from_secs_f64(self.done as f64 / rate as f64);
+        if let Some(ahead) = due.checked_sub(start.elapsed()) {
+            thread::sleep(ahead);
+        }
+    }
+}
+
+/// Reader going through a [`Throttle`], which may be shared by several readers in a row.
+pub(crate) struct Throttled<'a, R> {
+    inner: R,
+    throttle: &'a mut Throttle,
+}
+
+impl<'a, R> Throttled<'a, R> {
+    pub(crate) fn new(inner: R, throttle: &'a mut Throttle) -> Self {
+        Throttled { inner, throttle }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: Read> Read for Throttled<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.throttle.consume(n);
+        Ok(n)
+    }
+}
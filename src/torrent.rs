@@ -0,0 +1,244 @@
+//! BitTorrent v2 piece hashes and infohashes ([BEP 52](https://www.bittorrent.org/beps/bep_0052.html))
+//!
+//! Each file is split into 16 KiB blocks, the leaves of a Merkle tree padded with zero hashes
+//! to a power of 2. The root of that tree is the file's "pieces root", and the layer of the
+//! tree where each node covers one piece is its "piece layer".
+//!
+//! ```rust
+//! use sha256::torrent::{self, TorrentInfo};
+//! let hashes = torrent::piece_hashes("./foo.file", 16 * 1024).unwrap();
+//! assert_eq!(hashes.length, 12);
+//! assert!(hashes.piece_layer.is_empty());
+//! let info = TorrentInfo::from_path("./foo.file", 16 * 1024).unwrap();
+//! println!("{}", info.infohash());
+//! ```
+
+use crate::dir::Walk;
+use crate::{open_file, DirOptions, Error, Operation, Sha256Hash};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Size of the Merkle tree leaves, the smallest allowed piece length.
+pub const BLOCK_SIZE: usize = 16 * 1024;
+
+/// The v2 hashes of one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileHashes {
+    pub length: u64,
+    /// Root of the file's Merkle tree, `None` for empty files.
+    pub pieces_root: Option<Sha256Hash>,
+    /// One hash per piece, empty for files no longer than a piece.
+    pub piece_layer: Vec<Sha256Hash>,
+}
+
+/// The v2 hashes of the file at `path`, split into pieces of `piece_length` bytes
+///
+/// # Panics
+///
+/// If `piece_length` is not a power of 2 of at least [`BLOCK_SIZE`].
+pub fn piece_hashes<P: AsRef<Path>>(path: P, piece_length: u64) -> Result<FileHashes, Error> {
+    let path = path.as_ref();
+    let file = open_file(path)?;
+    hash_blocks(file, piece_length).map_err(|(e, offset)| Error::read(path, offset, e))
+}
+
+/// The v2 hashes of everything read from `reader`, split into pieces of `piece_length` bytes
+///
+/// # Panics
+///
+/// If `piece_length` is not a power of 2 of at least [`BLOCK_SIZE`].
+pub fn piece_hashes_reader<R: Read>(reader: R, piece_length: u64) -> io::Result<FileHashes> {
+    hash_blocks(reader, piece_length).map_err(|(e, _)| e)
+}
+
+fn hash_blocks<R: Read>(mut reader: R, piece_length: u64) -> Result<FileHashes, (io::Error, u64)> {
+    assert!(
+        piece_length.is_power_of_two() && piece_length >= BLOCK_SIZE as u64,
+        "piece_length must be a power of 2 of at least 16 KiB"
+    );
+    let mut leaves = Vec::new();
+    let mut length = 0;
+    let mut block = vec![0; BLOCK_SIZE];
+    loop {
+        let mut filled = 0;
+        while filled < BLOCK_SIZE {
+            match reader.read(&mut block[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err((e, length + filled as u64)),
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+        leaves.push(Sha256Hash::from(Sha256::digest(&block[..filled])));
+        length += filled as u64;
+        if filled < BLOCK_SIZE {
+            break;
+        }
+    }
+    if leaves.is_empty() {
+        return Ok(FileHashes {
+            length,
+            pieces_root: None,
+            piece_layer: Vec::new(),
+        });
+    }
+
+    let piece_level = (piece_length / BLOCK_SIZE as u64).trailing_zeros();
+    let mut level = leaves;
+    let mut pad = Sha256Hash::default();
+    let mut piece_layer = Vec::new();
+    let mut depth = 0;
+    while level.len() > 1 {
+        if depth == piece_level {
+            piece_layer = level.clone();
+        }
+        if level.len() % 2 == 1 {
+            level.push(pad);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| hash_pair(&pair[0], &pair[1]))
+            .collect();
+        pad = hash_pair(&pad, &pad);
+        depth += 1;
+    }
+    Ok(FileHashes {
+        length,
+        pieces_root: Some(level[0]),
+        piece_layer,
+    })
+}
+
+fn hash_pair(left: &Sha256Hash, right: &Sha256Hash) -> Sha256Hash {
+    Sha256::new_with_prefix(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+/// The v2 `info` dictionary of a file or directory, which the infohash is the digest of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentInfo {
+    pub name: String,
+    pub piece_length: u64,
+    /// Path components of each file relative to the torrent, and its hashes.
+    pub files: Vec<(Vec<String>, FileHashes)>,
+}
+
+impl TorrentInfo {
+    /// Hash the file or the files under the directory at `path`
+    ///
+    /// Files of a directory are visited the same way as by [`try_digest_dir`](crate::try_digest_dir).
+    ///
+    /// # Panics
+    ///
+    /// If `piece_length` is not a power of 2 of at least [`BLOCK_SIZE`].
+    pub fn from_path<P: AsRef<Path>>(path: P, piece_length: u64) -> Result<TorrentInfo, Error> {
+        let path = path.as_ref();
+        let canonical =
+            fs::canonicalize(path).map_err(|e| Error::io(Operation::Metadata, path, e))?;
+        let name = canonical
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut files = Vec::new();
+        for entry in Walk::with_options(path, DirOptions::new()) {
+            let entry = entry?;
+            let components = if entry.rel.as_os_str().is_empty() {
+                vec![name.clone()]
+            } else {
+                entry
+                    .rel
+                    .iter()
+                    .map(|c| c.to_string_lossy().into_owned())
+                    .collect()
+            };
+            files.push((components, piece_hashes(&entry.path, piece_length)?));
+        }
+        Ok(TorrentInfo {
+            name,
+            piece_length,
+            files,
+        })
+    }
+
+    /// The bencoded `info` dictionary.
+    pub fn info_bytes(&self) -> Vec<u8> {
+        let mut tree = BTreeMap::new();
+        for (components, hashes) in &self.files {
+            insert_file(&mut tree, components, hashes);
+        }
+
+        let mut out = b"d".to_vec();
+        bencode_str(&mut out, b"file tree");
+        bencode_tree(&mut out, &tree);
+        bencode_str(&mut out, b"meta version");
+        out.extend_from_slice(b"i2e");
+        bencode_str(&mut out, b"name");
+        bencode_str(&mut out, self.name.as_bytes());
+        bencode_str(&mut out, b"piece length");
+        out.extend_from_slice(format!("i{}e", self.piece_length).as_bytes());
+        out.push(b'e');
+        out
+    }
+
+    /// The v2 infohash, the sha256 of [`info_bytes`](TorrentInfo::info_bytes).
+    pub fn infohash(&self) -> Sha256Hash {
+        Sha256::digest(self.info_bytes()).into()
+    }
+}
+
+enum FileTree<'a> {
+    Dir(BTreeMap<&'a [u8], FileTree<'a>>),
+    File(&'a FileHashes),
+}
+
+fn insert_file<'a>(
+    dir: &mut BTreeMap<&'a [u8], FileTree<'a>>,
+    components: &'a [String],
+    hashes: &'a FileHashes,
+) {
+    match components.split_first() {
+        None => {
+            dir.insert(&b""[..], FileTree::File(hashes));
+        }
+        Some((first, rest)) => {
+            let node = dir
+                .entry(first.as_bytes())
+                .or_insert_with(|| FileTree::Dir(BTreeMap::new()));
+            if let FileTree::Dir(children) = node {
+                insert_file(children, rest, hashes);
+            }
+        }
+    }
+}
+
+fn bencode_str(out: &mut Vec<u8>, s: &[u8]) {
+    out.extend_from_slice(format!("{}:", s.len()).as_bytes());
+    out.extend_from_slice(s);
+}
+
+fn bencode_tree(out: &mut Vec<u8>, tree: &BTreeMap<&[u8], FileTree<'_>>) {
+    out.push(b'd');
+    for (name, node) in tree {
+        bencode_str(out, name);
+        match node {
+            FileTree::Dir(children) => bencode_tree(out, children),
+            FileTree::File(hashes) => {
+                out.extend_from_slice(format!("d6:lengthi{}e", hashes.length).as_bytes());
+                if let Some(root) = &hashes.pieces_root {
+                    bencode_str(out, b"pieces root");
+                    bencode_str(out, root.as_bytes());
+                }
+                out.push(b'e');
+            }
+        }
+    }
+    out.push(b'e');
+}
@@ -0,0 +1,122 @@
+use crate::Sha256Hash;
+use std::convert::TryInto;
+use std::fmt;
+
+/// An unsigned 256-bit integer, to treat digests as numbers
+///
+/// Digests convert big endian: the first byte of the digest is the most significant. Integers
+/// compare numerically, so a digest meets a difficulty target when its integer is at most the
+/// target.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{Sha256Hash, U256};
+/// let mut bytes = [0xff; 32];
+/// bytes[0] = 0;
+/// let hash = Sha256Hash::new(bytes);
+/// assert_eq!(hash.leading_zero_bits(), 8);
+/// assert!(hash.meets_target(&(U256::MAX >> 8)));
+/// assert!(!hash.meets_target(&(U256::MAX >> 9)));
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Self {
+        let mut limbs = [0; 4];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks(8)) {
+            *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(&self.0) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// The target encoded in the compact "nBits" form of Bitcoin block headers, `None` if it's
+    /// negative or doesn't fit in 256 bits.
+    pub fn from_compact(bits: u32) -> Option<Self> {
+        let exponent = (bits >> 24) as usize;
+        let mantissa = bits & 0x007f_ffff;
+        if bits & 0x0080_0000 != 0 && mantissa != 0 {
+            return None;
+        }
+        let mantissa = mantissa.to_be_bytes();
+        let mut bytes = [0u8; 32];
+        // The mantissa is the 3 most significant of `exponent` bytes.
+        for (i, &byte) in mantissa[1..].iter().enumerate() {
+            match (32 + i).checked_sub(exponent) {
+                Some(pos) if pos < 32 => bytes[pos] = byte,
+                Some(_) => {}
+                None if byte == 0 => {}
+                None => return None,
+            }
+        }
+        Some(U256::from_be_bytes(bytes))
+    }
+
+    pub fn leading_zeros(&self) -> u32 {
+        let mut zeros = 0;
+        for limb in &self.0 {
+            zeros += limb.leading_zeros();
+            if *limb != 0 {
+                break;
+            }
+        }
+        zeros
+    }
+}
+
+impl From<u64> for U256 {
+    fn from(n: u64) -> Self {
+        U256([0, 0, 0, n])
+    }
+}
+
+impl From<Sha256Hash> for U256 {
+    fn from(hash: Sha256Hash) -> Self {
+        U256::from_be_bytes(*hash.as_bytes())
+    }
+}
+
+impl std::ops::Shr<u32> for U256 {
+    type Output = U256;
+
+    /// Shift right, all zeros from 256 bits on.
+    fn shr(self, shift: u32) -> U256 {
+        if shift >= 256 {
+            return U256::ZERO;
+        }
+        let (limbs, bits) = ((shift / 64) as usize, shift % 64);
+        let mut out = [0; 4];
+        for (i, limb) in out.iter_mut().enumerate().skip(limbs) {
+            *limb = self.0[i - limbs] >> bits;
+            if bits > 0 && i > limbs {
+                *limb |= self.0[i - limbs - 1] << (64 - bits);
+            }
+        }
+        U256(out)
+    }
+}
+
+impl fmt::Display for U256 {
+    /// Lowercase hex, all 64 digits.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::lower_hex::encode(self.to_be_bytes()))
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "U256({})", self)
+    }
+}
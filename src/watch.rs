@@ -0,0 +1,102 @@
+use crate::dir::Walk;
+use crate::{calc_file, open_file, DirOptions, Error, Operation, Sha256Hash};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+type Digests = Arc<Mutex<BTreeMap<PathBuf, Sha256Hash>>>;
+
+/// Keeps the digests of every file under a directory up to date
+///
+/// The tree is hashed once when the watcher is created, then only the files reported as changed
+/// by the OS are hashed again. Paths are absolute, under the canonical root.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sha256::DigestWatcher;
+/// let watcher = DigestWatcher::new("./src").unwrap();
+/// for (path, hash) in watcher.digests() {
+///     println!("{}  {}", hash, path.display());
+/// }
+/// ```
+pub struct DigestWatcher {
+    root: PathBuf,
+    digests: Digests,
+    _watcher: RecommendedWatcher,
+}
+
+impl DigestWatcher {
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self, Error> {
+        let root = root.as_ref();
+        let root = fs::canonicalize(root).map_err(|e| Error::io(Operation::Metadata, root, e))?;
+        let digests = Digests::default();
+
+        // Watch before the first scan, so that no change goes unnoticed in between.
+        let shared = digests.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                for path in event.paths {
+                    refresh(&shared, &path);
+                }
+            }
+        })?;
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+
+        for entry in Walk::with_options(&root, DirOptions::new()) {
+            let path = entry?.path;
+            let hash = hash_file(&path)?;
+            digests.lock().unwrap().entry(path).or_insert(hash);
+        }
+        Ok(DigestWatcher {
+            root,
+            digests,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The current digest of the file at an absolute `path`.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<Sha256Hash> {
+        self.digests.lock().unwrap().get(path.as_ref()).copied()
+    }
+
+    /// A snapshot of all the digests.
+    pub fn digests(&self) -> BTreeMap<PathBuf, Sha256Hash> {
+        self.digests.lock().unwrap().clone()
+    }
+}
+
+/// Bring the digests at and under `path` in line with the file system.
+fn refresh(digests: &Digests, path: &Path) {
+    let is_dir = fs::metadata(path).map(|meta| meta.is_dir());
+    let mut fresh = BTreeMap::new();
+    match is_dir {
+        Ok(true) => {
+            for entry in Walk::with_options(path, DirOptions::new()).flatten() {
+                if let Ok(hash) = hash_file(&entry.path) {
+                    fresh.insert(entry.path, hash);
+                }
+            }
+        }
+        Ok(false) => {
+            if let Ok(hash) = hash_file(path) {
+                fresh.insert(path.to_path_buf(), hash);
+            }
+        }
+        Err(_) => {}
+    }
+    let mut digests = digests.lock().unwrap();
+    digests.retain(|p, _| !p.starts_with(path));
+    digests.extend(fresh);
+}
+
+fn hash_file(path: &Path) -> Result<Sha256Hash, Error> {
+    let f = open_file(path)?;
+    Ok(calc_file(path, f)?.into())
+}
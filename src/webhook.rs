@@ -0,0 +1,130 @@
+use crate::hash::ct_eq;
+use crate::{hmac, hmac_raw, HmacSha256};
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Check the `X-Hub-Signature-256` header GitHub sends with webhook deliveries
+///
+/// The header is `sha256=` followed by the hex HMAC-SHA256 of the raw request body under the
+/// webhook secret. The body must be the exact bytes received, before any JSON parsing.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::verify_github_signature;
+/// let header = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+/// assert!(verify_github_signature("It's a Secret to Everybody", "Hello, World!", header));
+/// assert!(!verify_github_signature("wrong secret", "Hello, World!", header));
+/// ```
+pub fn verify_github_signature<S, B>(secret: S, body: B, header: &str) -> bool
+where
+    S: AsRef<[u8]>,
+    B: AsRef<[u8]>,
+{
+    let expected = match header.trim().strip_prefix("sha256=") {
+        Some(hex) => hex,
+        None => return false,
+    };
+    let mut expected_bytes = [0u8; 32];
+    if hex::decode_to_slice(expected, &mut expected_bytes).is_err() {
+        return false;
+    }
+    ct_eq(&hmac_raw(secret, body), &expected_bytes)
+}
+
+/// Why [`verify_timestamped_signature`] rejected a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookError {
+    /// The header has no `t=` timestamp or no `v1=` signature.
+    Malformed,
+    /// The timestamp is further from now than the tolerance.
+    Expired,
+    /// No `v1=` signature matches.
+    Signature,
+}
+
+impl fmt::Display for WebhookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WebhookError::Malformed => "malformed signature header",
+            WebhookError::Expired => "signature timestamp outside of the tolerance",
+            WebhookError::Signature => "no matching signature",
+        })
+    }
+}
+
+impl std::error::Error for WebhookError {}
+
+/// Check a Stripe-style `t=<timestamp>,v1=<signature>` webhook signature header
+///
+/// Each `v1` signature is the hex HMAC-SHA256 of `"<timestamp>.<payload>"` under the secret, one
+/// matching is enough, other schemes like `v0` are ignored. The timestamp, in seconds since the
+/// unix epoch, must be within `tolerance` of now, which stops replays of old requests.
+///
+/// # Examples
+///
+/// ```rust
+/// use sha256::{sign_timestamped, verify_timestamped_signature};
+/// use std::time::{Duration, SystemTime};
+/// let header = sign_timestamped("whsec", "{}", SystemTime::now());
+/// assert!(verify_timestamped_signature("whsec", "{}", &header, Duration::from_secs(300)).is_ok());
+/// ```
+pub fn verify_timestamped_signature<S, P>(
+    secret: S,
+    payload: P,
+    header: &str,
+    tolerance: Duration,
+) -> Result<(), WebhookError>
+where
+    S: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+{
+    let mut timestamp = None;
+    let mut signatures = vec![];
+    for item in header.split(',') {
+        match item.trim().split_once('=') {
+            Some(("t", t)) => timestamp = t.parse::<u64>().ok().map(|secs| (t, secs)),
+            Some(("v1", sig)) => signatures.push(sig),
+            _ => {}
+        }
+    }
+    let (raw_timestamp, timestamp) = timestamp.ok_or(WebhookError::Malformed)?;
+    if signatures.is_empty() {
+        return Err(WebhookError::Malformed);
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    if now.abs_diff(timestamp) > tolerance.as_secs() {
+        return Err(WebhookError::Expired);
+    }
+    // The sender signed the timestamp as it wrote it, which needn't be how we'd format it.
+    let mut mac = HmacSha256::new(secret);
+    mac.update(raw_timestamp);
+    mac.update(".");
+    mac.update(payload);
+    let expected = mac.finalize();
+    let mut matched = false;
+    for sig in signatures {
+        let mut bytes = [0u8; 32];
+        // Check them all, so the time taken doesn't tell which one matched.
+        matched |= hex::decode_to_slice(sig, &mut bytes).is_ok() && ct_eq(&bytes, &expected);
+    }
+    if matched {
+        Ok(())
+    } else {
+        Err(WebhookError::Signature)
+    }
+}
+
+/// The `t=<timestamp>,v1=<signature>` header [`verify_timestamped_signature`] checks
+pub fn sign_timestamped<S, P>(secret: S, payload: P, time: SystemTime) -> String
+where
+    S: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+{
+    let timestamp = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let mut signed = format!("{}.", timestamp).into_bytes();
+    signed.extend_from_slice(payload.as_ref());
+    format!("t={},v1={}", timestamp, hmac(secret, signed))
+}